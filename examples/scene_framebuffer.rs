@@ -0,0 +1,35 @@
+use ferrousgl::{GlWindow, PostEffect, PostProcessStack, RenderTexture, WindowConfig};
+use glam::Vec4;
+
+// Renders into the window's scene framebuffer while also creating render textures after the window
+fn main() {
+    let mut window = GlWindow::new(WindowConfig {
+        width: 800,
+        height: 600,
+        title: "Scene Framebuffer Example".to_owned(),
+        scene_framebuffer: true,
+        anti_aliasing: 4,
+        ..Default::default()
+    });
+
+    let (width, height) = window.get_framebuffer_size();
+    let _offscreen = RenderTexture::new(256, 256, true).unwrap();
+    let mut post_process = PostProcessStack::new(width as u32, height as u32).unwrap();
+    post_process.push(PostEffect::Bloom {
+        threshold: 0.8,
+        intensity: 0.5,
+        iterations: 4,
+    });
+
+    let mut time = 0.0f32;
+    while !window.should_window_close() {
+        time += 0.01;
+
+        post_process.begin();
+        window.clear_color(Vec4::new(time.sin() * 0.5 + 0.5, 0.3, 0.6, 1.0));
+        window.clear_depth();
+        post_process.end().unwrap();
+
+        window.update();
+    }
+}
@@ -1,5 +1,5 @@
-use ferrousgl::{texture::FilterMode, DepthType, GlWindow, Mesh, MipmapType, RenderTexture, Shader, Texture, WindowConfig};
-use glam::{Vec3, Vec4, Mat4};
+use ferrousgl::{texture::FilterMode, DepthType, GlWindow, Mesh, MipmapType, RenderTexture, Scene, Shader, Texture, Transform, WindowConfig};
+use glam::{Mat4, Quat, Vec3, Vec4};
 use std::{path::Path, time::Instant};
 
 fn main() {
@@ -125,6 +125,20 @@ fn main() {
     let light_up = Vec3::new(0.0, 1.0, 0.0);
     let light_view = Mat4::look_at_rh(light_pos, light_target, light_up);
 
+    // Scene nodes holding the object transforms
+    let mut scene = Scene::new();
+    let floor_node = scene.add_node(
+        "floor",
+        Transform::from_translation(Vec3::new(0.0, -1.0, 0.0)).with_scale(Vec3::new(2.0, 1.0, 2.0)),
+        None,
+    );
+    let podest_node = scene.add_node(
+        "podest",
+        Transform::from_translation(Vec3::new(1.0, -1.25, 0.0)).with_scale(Vec3::new(2.0, 0.5, 2.0)),
+        None,
+    );
+    let cube_node = scene.add_node("cube", Transform::from_translation(Vec3::new(1.0, 0.0, 0.0)), None);
+
     let mut y_rotation = 0.0f32;
     let mut x_rotation = 0.0f32;
 
@@ -158,13 +172,10 @@ fn main() {
         
         y_rotation += 0.01;
         x_rotation += 0.005;
-        let cube_model = Mat4::from_translation(Vec3::new(1.0, 0.0, 0.0)) * 
-                         Mat4::from_rotation_y(y_rotation) * 
-                         Mat4::from_rotation_x(x_rotation);
-        let podest_model = Mat4::from_translation(Vec3::new(1.0, -1.25, 0.0)) *
-        Mat4::from_scale(Vec3::new(2.0, 0.5, 2.0));
-        let floor_model = Mat4::from_translation(Vec3::new(0.0, -1.0, 0.0)) * 
-                          Mat4::from_scale(Vec3::new(2.0, 1.0, 2.0));
+        scene.transform_mut(cube_node).rotation = Quat::from_rotation_y(y_rotation) * Quat::from_rotation_x(x_rotation);
+        let cube_model = scene.world_matrix(cube_node);
+        let podest_model = scene.world_matrix(podest_node);
+        let floor_model = scene.world_matrix(floor_node);
         
         depth_shader.bind_program();
         depth_shader.set_uniform_matrix_4fv("lightSpaceMatrix", 
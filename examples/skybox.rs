@@ -0,0 +1,86 @@
+use ferrousgl::{CubemapTexture, GlWindow, Mesh, Shader, Skybox, Texture, WindowConfig};
+use glam::{Mat4, Vec3, Vec4};
+use std::path::Path;
+
+fn main() {
+    let mut window = GlWindow::new(WindowConfig {
+        width: 800,
+        height: 600,
+        title: "Skybox Example".to_owned(),
+        ..Default::default()
+    });
+
+    let shader = Shader::new_from_file(
+        Path::new("./examples/shaders/textured_cube/vertex.glsl"),
+        Path::new("./examples/shaders/textured_cube/fragment.glsl"),
+    ).unwrap();
+
+    let texture = Texture::new_from_file(Path::new("examples/assets/wood_texture.png")).unwrap();
+
+    // Any panorama image can be converted into a cubemap on the GPU
+    let cubemap = CubemapTexture::new_from_equirectangular_file(
+        Path::new("examples/assets/cool_image.png"),
+        512,
+    ).unwrap();
+    let skybox = Skybox::new();
+
+    let mut mesh = Mesh::new();
+
+    let vertices = [
+        // positions        // texture coords
+         0.5,  0.5, -0.5,   1.0, 1.0,
+         0.5, -0.5, -0.5,   1.0, 0.0,
+        -0.5, -0.5, -0.5,   0.0, 0.0,
+        -0.5,  0.5, -0.5,   0.0, 1.0,
+         0.5,  0.5,  0.5,   0.0, 0.0,
+         0.5, -0.5,  0.5,   0.0, 1.0,
+        -0.5, -0.5,  0.5,   1.0, 1.0,
+        -0.5,  0.5,  0.5,   1.0, 0.0
+    ];
+
+    let indices = [
+        0, 1, 3,  1, 2, 3,  // bottom
+        4, 5, 7,  5, 6, 7,  // top
+        4, 0, 7,  0, 3, 7,  // front
+        5, 1, 6,  1, 2, 6,  // back
+        4, 5, 0,  5, 1, 0,  // right
+        7, 6, 3,  6, 2, 3   // left
+    ];
+
+    mesh.update_vertices(&vertices);
+    mesh.update_indices(&indices);
+    mesh.add_vertex_attributes(&[
+        (0, 3, gl::FLOAT, false),  // position
+        (1, 2, gl::FLOAT, false)   // texture coord
+    ]);
+
+    let mut camera_angle = 0.0f32;
+
+    while !window.should_window_close() {
+        camera_angle += 0.005;
+
+        let (width, height) = window.get_window_size();
+        let projection = Mat4::perspective_rh_gl(60.0f32.to_radians(), width as f32 / height as f32, 0.1, 100.0);
+        let camera_pos = Vec3::new(camera_angle.sin() * 3.0, 1.0, camera_angle.cos() * 3.0);
+        let view = Mat4::look_at_rh(camera_pos, Vec3::ZERO, Vec3::Y);
+
+        window.clear_color(Vec4::new(0.0, 0.0, 0.0, 1.0));
+        window.clear_depth();
+
+        // Draw the scene first
+        texture.bind(0);
+        shader.bind_program();
+        shader.set_uniform_texture("ourTexture", 0);
+        shader.set_uniform_matrix_4fv("projection", projection.to_cols_array().as_ref());
+        shader.set_uniform_matrix_4fv("view", view.to_cols_array().as_ref());
+        shader.set_uniform_matrix_4fv("model", Mat4::IDENTITY.to_cols_array().as_ref());
+        window.render_mesh(&mesh);
+        shader.unbind_program();
+        texture.unbind();
+
+        // Then fill the remaining background with the sky
+        skybox.render(&window, &cubemap, view, projection);
+
+        window.update();
+    }
+}
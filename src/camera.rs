@@ -0,0 +1,336 @@
+extern crate glam;
+extern crate glfw;
+
+use glam::{EulerRot, Mat4, Quat, Vec3};
+
+use crate::window::WindowKey;
+use crate::GlWindow;
+
+/// How a camera projects the scene onto the screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// A perspective projection with a vertical field of view in radians.
+    Perspective { fov_y: f32 },
+    /// An orthographic projection showing `height` world units vertically, the width follows the aspect ratio.
+    Orthographic { height: f32 },
+}
+
+/// A camera with a position, orientation and projection, producing view and projection matrices for shaders.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    pub position: Vec3,
+    pub rotation: Quat,
+    pub projection: Projection,
+    pub near: f32,
+    pub far: f32,
+    /// Maps the near plane to depth 1 and the far plane to 0, which spreads float depth precision much more evenly.
+    /// Needs [`GlWindow::set_reverse_z`] and works best with a float depth buffer.
+    pub reverse_z: bool,
+}
+
+impl Camera {
+    /// Creates a perspective camera at the origin looking down -Z, with the vertical field of view in degrees.
+    pub fn new_perspective(fov_y_degrees: f32, near: f32, far: f32) -> Self {
+        Camera {
+            position: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            projection: Projection::Perspective {
+                fov_y: fov_y_degrees.to_radians(),
+            },
+            near,
+            far,
+            reverse_z: false,
+        }
+    }
+
+    /// Creates an orthographic camera at the origin looking down -Z, showing `height` world units vertically.
+    pub fn new_orthographic(height: f32, near: f32, far: f32) -> Self {
+        Camera {
+            position: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            projection: Projection::Orthographic { height },
+            near,
+            far,
+            reverse_z: false,
+        }
+    }
+
+    /// Turns the camera to look at a point, keeping +Y as up.
+    pub fn look_at(&mut self, target: Vec3) {
+        let view = Mat4::look_at_rh(self.position, target, Vec3::Y);
+        self.rotation = Quat::from_mat4(&view.inverse());
+    }
+
+    /// Returns the direction the camera is looking in.
+    pub fn forward(&self) -> Vec3 {
+        self.rotation * Vec3::NEG_Z
+    }
+
+    /// Returns the direction to the right of the camera.
+    pub fn right(&self) -> Vec3 {
+        self.rotation * Vec3::X
+    }
+
+    /// Returns the up direction of the camera.
+    pub fn up(&self) -> Vec3 {
+        self.rotation * Vec3::Y
+    }
+
+    /// Returns the view matrix, which transforms world space into camera space.
+    pub fn view_matrix(&self) -> Mat4 {
+        Mat4::from_rotation_translation(self.rotation, self.position).inverse()
+    }
+
+    /// Returns the projection matrix for the given aspect ratio (width / height).
+    pub fn projection_matrix(&self, aspect_ratio: f32) -> Mat4 {
+        match (self.projection, self.reverse_z) {
+            (Projection::Perspective { fov_y }, false) => {
+                Mat4::perspective_rh_gl(fov_y, aspect_ratio, self.near, self.far)
+            }
+            // Swapping near and far of a 0 to 1 depth projection reverses it
+            (Projection::Perspective { fov_y }, true) => {
+                Mat4::perspective_rh(fov_y, aspect_ratio, self.far, self.near)
+            }
+            (Projection::Orthographic { height }, reverse_z) => {
+                let half_height = height * 0.5;
+                let half_width = half_height * aspect_ratio;
+                if reverse_z {
+                    Mat4::orthographic_rh(-half_width, half_width, -half_height, half_height, self.far, self.near)
+                } else {
+                    Mat4::orthographic_rh_gl(-half_width, half_width, -half_height, half_height, self.near, self.far)
+                }
+            }
+        }
+    }
+
+    /// Returns the projection matrix matching the aspect ratio of the window's framebuffer.
+    pub fn projection_matrix_for_window(&self, window: &GlWindow) -> Mat4 {
+        let (width, height) = window.get_framebuffer_size();
+        self.projection_matrix(width.max(1) as f32 / height.max(1) as f32)
+    }
+
+    /// Returns the projection matrix for the window multiplied with the view matrix.
+    pub fn view_projection_matrix(&self, window: &GlWindow) -> Mat4 {
+        self.projection_matrix_for_window(window) * self.view_matrix()
+    }
+}
+
+/// Orbits a camera around a target point. Dragging with the mouse button rotates and the mouse wheel zooms.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrbitController {
+    pub target: Vec3,
+    /// The angle around the Y axis in radians.
+    pub yaw: f32,
+    /// The angle above the horizon in radians.
+    pub pitch: f32,
+    pub distance: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    /// Radians of rotation per pixel of mouse movement.
+    pub rotate_sensitivity: f32,
+    /// How much the distance changes per mouse wheel step, as a fraction of the distance.
+    pub zoom_sensitivity: f32,
+    /// The time in seconds it takes to get most of the way to the new orientation, 0 for no smoothing.
+    pub smoothing: f32,
+    /// The mouse button that has to be held to rotate.
+    pub rotate_button: glfw::MouseButton,
+    current_yaw: f32,
+    current_pitch: f32,
+    current_distance: f32,
+}
+
+impl OrbitController {
+    /// Creates a new orbit controller looking at `target` from `distance` away.
+    pub fn new(target: Vec3, distance: f32) -> Self {
+        OrbitController {
+            target,
+            yaw: 0.0,
+            pitch: 0.3,
+            distance,
+            min_distance: 0.1,
+            max_distance: 1000.0,
+            rotate_sensitivity: 0.005,
+            zoom_sensitivity: 0.1,
+            smoothing: 0.08,
+            rotate_button: glfw::MouseButton::Button1,
+            current_yaw: 0.0,
+            current_pitch: 0.3,
+            current_distance: distance,
+        }
+    }
+
+    /// Applies this frame's mouse input and moves the camera. `delta_time` is the frame time in seconds.
+    pub fn update(&mut self, window: &GlWindow, camera: &mut Camera, delta_time: f32) {
+        if window.is_mouse_button_pressed(self.rotate_button) {
+            let (dx, dy) = window.get_mouse_delta();
+            self.yaw -= dx as f32 * self.rotate_sensitivity;
+            self.pitch += dy as f32 * self.rotate_sensitivity;
+        }
+
+        let (_, wheel) = window.get_mouse_wheel_delta();
+        self.distance *= (1.0 - wheel as f32 * self.zoom_sensitivity).max(0.1);
+
+        // Stop just short of straight up or down, where the orientation would flip
+        let pitch_limit = std::f32::consts::FRAC_PI_2 - 0.01;
+        self.pitch = self.pitch.clamp(-pitch_limit, pitch_limit);
+        self.distance = self.distance.clamp(self.min_distance, self.max_distance);
+
+        let t = smoothing_factor(self.smoothing, delta_time);
+        self.current_yaw += (self.yaw - self.current_yaw) * t;
+        self.current_pitch += (self.pitch - self.current_pitch) * t;
+        self.current_distance += (self.distance - self.current_distance) * t;
+
+        self.apply(camera);
+    }
+
+    /// Places the camera according to the current (smoothed) orientation without handling input.
+    pub fn apply(&self, camera: &mut Camera) {
+        let rotation = Quat::from_euler(EulerRot::YXZ, self.current_yaw, -self.current_pitch, 0.0);
+        camera.rotation = rotation;
+        camera.position = self.target - rotation * Vec3::NEG_Z * self.current_distance;
+    }
+}
+
+/// Flies a camera freely. WASD moves, Space and Left Control move up and down, Left Shift moves faster,
+/// and the mouse looks around while the look button is held.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlyController {
+    /// The angle around the Y axis in radians.
+    pub yaw: f32,
+    /// The angle above the horizon in radians.
+    pub pitch: f32,
+    /// Movement speed in units per second.
+    pub speed: f32,
+    /// How much faster the camera moves while Left Shift is held.
+    pub boost_multiplier: f32,
+    /// Radians of rotation per pixel of mouse movement.
+    pub look_sensitivity: f32,
+    /// The time in seconds it takes to get most of the way to the new velocity and orientation, 0 for no smoothing.
+    pub smoothing: f32,
+    /// The mouse button that has to be held to look around, or `None` to always follow the mouse.
+    pub look_button: Option<glfw::MouseButton>,
+    current_yaw: f32,
+    current_pitch: f32,
+    velocity: Vec3,
+}
+
+impl FlyController {
+    /// Creates a new fly controller, taking the initial orientation from the camera.
+    pub fn new(camera: &Camera) -> Self {
+        let (yaw, pitch, _) = camera.rotation.to_euler(EulerRot::YXZ);
+
+        FlyController {
+            yaw,
+            pitch,
+            speed: 5.0,
+            boost_multiplier: 4.0,
+            look_sensitivity: 0.003,
+            smoothing: 0.05,
+            look_button: Some(glfw::MouseButton::Button2),
+            current_yaw: yaw,
+            current_pitch: pitch,
+            velocity: Vec3::ZERO,
+        }
+    }
+
+    /// Applies this frame's keyboard and mouse input and moves the camera. `delta_time` is the frame time in seconds.
+    pub fn update(&mut self, window: &GlWindow, camera: &mut Camera, delta_time: f32) {
+        let looking = self
+            .look_button
+            .is_none_or(|button| window.is_mouse_button_pressed(button));
+        if looking {
+            let (dx, dy) = window.get_mouse_delta();
+            self.yaw -= dx as f32 * self.look_sensitivity;
+            self.pitch -= dy as f32 * self.look_sensitivity;
+        }
+
+        let pitch_limit = std::f32::consts::FRAC_PI_2 - 0.01;
+        self.pitch = self.pitch.clamp(-pitch_limit, pitch_limit);
+
+        let t = smoothing_factor(self.smoothing, delta_time);
+        self.current_yaw += (self.yaw - self.current_yaw) * t;
+        self.current_pitch += (self.pitch - self.current_pitch) * t;
+        camera.rotation = Quat::from_euler(EulerRot::YXZ, self.current_yaw, self.current_pitch, 0.0);
+
+        let axis = |positive: WindowKey, negative: WindowKey| {
+            window.is_key_held(positive) as i32 as f32 - window.is_key_held(negative) as i32 as f32
+        };
+        let direction = camera.forward() * axis(WindowKey::W, WindowKey::S)
+            + camera.right() * axis(WindowKey::D, WindowKey::A)
+            + Vec3::Y * axis(WindowKey::Space, WindowKey::LeftControl);
+
+        let mut target_velocity = direction.normalize_or_zero() * self.speed;
+        if window.is_key_held(WindowKey::LeftShift) {
+            target_velocity *= self.boost_multiplier;
+        }
+
+        self.velocity += (target_velocity - self.velocity) * t;
+        camera.position += self.velocity * delta_time;
+    }
+}
+
+/// Returns how far to move towards a target this frame for frame rate independent exponential smoothing.
+fn smoothing_factor(smoothing: f32, delta_time: f32) -> f32 {
+    if smoothing <= 0.0 {
+        1.0
+    } else {
+        1.0 - (-delta_time / smoothing).exp()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec4;
+
+    /// Returns the depth a camera space point ends up at after the perspective divide.
+    fn depth(projection: Mat4, distance: f32) -> f32 {
+        let clip = projection * Vec4::new(0.0, 0.0, -distance, 1.0);
+        clip.z / clip.w
+    }
+
+    #[test]
+    fn reverse_z_maps_near_to_one_and_far_to_zero() {
+        let mut perspective = Camera::new_perspective(60.0, 0.1, 100.0);
+        let mut orthographic = Camera::new_orthographic(10.0, 0.1, 100.0);
+        perspective.reverse_z = true;
+        orthographic.reverse_z = true;
+
+        for camera in [perspective, orthographic] {
+            let projection = camera.projection_matrix(16.0 / 9.0);
+            assert!((depth(projection, camera.near) - 1.0).abs() < 1e-5, "{:?}", camera.projection);
+            assert!(depth(projection, camera.far).abs() < 1e-5, "{:?}", camera.projection);
+        }
+    }
+
+    #[test]
+    fn default_depth_maps_near_to_minus_one_and_far_to_one() {
+        for camera in [Camera::new_perspective(60.0, 0.1, 100.0), Camera::new_orthographic(10.0, 0.1, 100.0)] {
+            let projection = camera.projection_matrix(1.0);
+            assert!((depth(projection, camera.near) + 1.0).abs() < 1e-4, "{:?}", camera.projection);
+            assert!((depth(projection, camera.far) - 1.0).abs() < 1e-4, "{:?}", camera.projection);
+        }
+    }
+
+    #[test]
+    fn look_at_points_forward_at_the_target() {
+        let mut camera = Camera::new_perspective(60.0, 0.1, 100.0);
+        camera.position = Vec3::new(3.0, 2.0, 5.0);
+        camera.look_at(Vec3::ZERO);
+
+        let expected = (Vec3::ZERO - camera.position).normalize();
+        assert!(camera.forward().abs_diff_eq(expected, 1e-5));
+        assert!(camera.right().y.abs() < 1e-5);
+        assert!((camera.view_matrix() * camera.position.extend(1.0)).truncate().abs_diff_eq(Vec3::ZERO, 1e-5));
+    }
+
+    #[test]
+    fn smoothing_factor_is_frame_rate_independent() {
+        assert_eq!(smoothing_factor(0.0, 0.016), 1.0);
+
+        // Two half steps cover the same distance as one full step
+        let full = smoothing_factor(0.1, 0.032);
+        let half = smoothing_factor(0.1, 0.016);
+        assert!((1.0 - (1.0 - half) * (1.0 - half) - full).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,375 @@
+use std::fs;
+use std::path::Path;
+
+use crate::mipmap::mip_level_count;
+use crate::texture::TextureFormat;
+
+const DDS_MAGIC: &[u8; 4] = b"DDS ";
+const DDS_HEADER_SIZE: usize = 4 + 124;
+const DDS_DX10_HEADER_SIZE: usize = 20;
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDPF_FOURCC: u32 = 0x4;
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+const DDSCAPS2_VOLUME: u32 = 0x200000;
+
+const KTX2_IDENTIFIER: [u8; 12] = [0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A];
+const KTX2_HEADER_SIZE: usize = 80;
+
+/// A precompressed (BCn) image with its mip chain, as stored in a DDS or KTX2 container.
+/// Upload it with [`crate::Texture::from_compressed`], or simply use [`crate::Texture::new_from_file`].
+#[derive(Debug, Clone)]
+pub struct CompressedImage {
+    pub width: u32,
+    pub height: u32,
+    pub format: TextureFormat,
+    /// The compressed data of each mip level, starting with the full size image.
+    pub levels: Vec<Vec<u8>>,
+}
+
+impl CompressedImage {
+    /// Loads a DDS or KTX2 file, picking the container from the file extension.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let bytes = fs::read(path)
+            .map_err(|e| format!("[FerrousGl Error] Failed to read compressed texture file: {}", e))?;
+
+        match extension(path).as_deref() {
+            Some("dds") => Self::from_dds_bytes(&bytes),
+            Some("ktx2") => Self::from_ktx2_bytes(&bytes),
+            _ => Err(format!(
+                "[FerrousGl Error] {} is not a DDS or KTX2 file.",
+                path.display()
+            )),
+        }
+    }
+
+    /// Returns true if the file extension belongs to a supported compressed texture container.
+    pub fn is_container(path: &Path) -> bool {
+        matches!(extension(path).as_deref(), Some("dds") | Some("ktx2"))
+    }
+
+    /// Parses a DDS file containing a single BC1 to BC7 compressed 2D texture.
+    pub fn from_dds_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < DDS_HEADER_SIZE || &bytes[0..4] != DDS_MAGIC {
+            return Err("[FerrousGl Error] Not a valid DDS file.".to_string());
+        }
+
+        let flags = read_u32(bytes, 8)?;
+        let height = read_u32(bytes, 12)?;
+        let width = read_u32(bytes, 16)?;
+        let mip_count = read_u32(bytes, 28)?;
+        let pixel_format_flags = read_u32(bytes, 80)?;
+        let four_cc = &bytes[84..88];
+        let caps2 = read_u32(bytes, 112)?;
+
+        if caps2 & (DDSCAPS2_CUBEMAP | DDSCAPS2_VOLUME) != 0 {
+            return Err("[FerrousGl Error] DDS cubemaps and volume textures are not supported.".to_string());
+        }
+
+        if pixel_format_flags & DDPF_FOURCC == 0 {
+            return Err("[FerrousGl Error] Only block compressed DDS files are supported, use an image file for uncompressed textures.".to_string());
+        }
+
+        let (format, data_offset) = if four_cc == b"DX10" {
+            let dxgi_format = read_u32(bytes, DDS_HEADER_SIZE)?;
+            let array_size = read_u32(bytes, DDS_HEADER_SIZE + 12)?;
+            if array_size > 1 {
+                return Err("[FerrousGl Error] DDS texture arrays are not supported.".to_string());
+            }
+            (format_from_dxgi(dxgi_format)?, DDS_HEADER_SIZE + DDS_DX10_HEADER_SIZE)
+        } else {
+            (format_from_four_cc(four_cc)?, DDS_HEADER_SIZE)
+        };
+
+        check_dimensions(width, height)?;
+        let declared_levels = if flags & DDSD_MIPMAPCOUNT != 0 { mip_count.max(1) } else { 1 };
+        let level_count = declared_levels.min(mip_level_count(width, height));
+
+        let mut level_sizes = Vec::new();
+        let mut end = data_offset;
+        for level in 0..level_count {
+            let size = level_size(format, width >> level, height >> level)
+                .ok_or("[FerrousGl Error] DDS texture is too large.")?;
+            end = end
+                .checked_add(size)
+                .filter(|&end| end <= bytes.len())
+                .ok_or("[FerrousGl Error] DDS file is truncated.")?;
+            level_sizes.push(size);
+        }
+
+        let mut levels = Vec::with_capacity(level_sizes.len());
+        let mut offset = data_offset;
+        for size in level_sizes {
+            levels.push(bytes[offset..offset + size].to_vec());
+            offset += size;
+        }
+
+        Ok(CompressedImage {
+            width,
+            height,
+            format,
+            levels,
+        })
+    }
+
+    /// Parses a KTX2 file containing a single BC1 to BC7 compressed 2D texture without supercompression.
+    pub fn from_ktx2_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < KTX2_HEADER_SIZE || bytes[0..12] != KTX2_IDENTIFIER {
+            return Err("[FerrousGl Error] Not a valid KTX2 file.".to_string());
+        }
+
+        let vk_format = read_u32(bytes, 12)?;
+        let width = read_u32(bytes, 20)?;
+        let height = read_u32(bytes, 24)?;
+        let depth = read_u32(bytes, 28)?;
+        let layer_count = read_u32(bytes, 32)?;
+        let face_count = read_u32(bytes, 36)?;
+        let level_count = read_u32(bytes, 40)?.max(1);
+        let supercompression = read_u32(bytes, 44)?;
+
+        if supercompression != 0 {
+            return Err("[FerrousGl Error] Supercompressed (Basis Universal or Zstandard) KTX2 files are not supported.".to_string());
+        }
+
+        if depth > 0 || layer_count > 1 || face_count != 1 {
+            return Err("[FerrousGl Error] Only 2D KTX2 textures are supported, not cubemaps, arrays or volumes.".to_string());
+        }
+
+        let format = format_from_vk(vk_format)?;
+        check_dimensions(width, height)?;
+
+        let level_count = level_count.min(mip_level_count(width, height)) as usize;
+        if KTX2_HEADER_SIZE + level_count * 24 > bytes.len() {
+            return Err("[FerrousGl Error] KTX2 file is truncated.".to_string());
+        }
+
+        let mut levels = Vec::with_capacity(level_count);
+        for level in 0..level_count {
+            let index_offset = KTX2_HEADER_SIZE + level * 24;
+            let offset = read_u64(bytes, index_offset)?;
+            let length = read_u64(bytes, index_offset + 8)?;
+
+            let data = usize::try_from(offset)
+                .ok()
+                .zip(usize::try_from(length).ok())
+                .and_then(|(offset, length)| bytes.get(offset..offset.checked_add(length)?))
+                .ok_or("[FerrousGl Error] KTX2 file is truncated.")?;
+            levels.push(data.to_vec());
+        }
+
+        Ok(CompressedImage {
+            width,
+            height,
+            format,
+            levels,
+        })
+    }
+}
+
+/// Returns the size in bytes of one compressed mip level, or None if it doesn't fit in memory.
+fn level_size(format: TextureFormat, width: u32, height: u32) -> Option<usize> {
+    let blocks_wide = width.max(1).div_ceil(4) as usize;
+    let blocks_high = height.max(1).div_ceil(4) as usize;
+    blocks_wide
+        .checked_mul(blocks_high)?
+        .checked_mul(format.block_size().unwrap_or(16))
+}
+
+fn check_dimensions(width: u32, height: u32) -> Result<(), String> {
+    if width == 0 || height == 0 || width > i32::MAX as u32 || height > i32::MAX as u32 {
+        return Err(format!(
+            "[FerrousGl Error] Invalid compressed texture size {}x{}.",
+            width, height
+        ));
+    }
+    Ok(())
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase())
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, String> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| "[FerrousGl Error] Compressed texture header is truncated.".to_string())
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Result<u64, String> {
+    Ok(read_u32(bytes, offset)? as u64 | (read_u32(bytes, offset + 4)? as u64) << 32)
+}
+
+fn format_from_four_cc(four_cc: &[u8]) -> Result<TextureFormat, String> {
+    match four_cc {
+        b"DXT1" => Ok(TextureFormat::Bc1),
+        b"DXT2" | b"DXT3" => Ok(TextureFormat::Bc2),
+        b"DXT4" | b"DXT5" => Ok(TextureFormat::Bc3),
+        b"ATI1" | b"BC4U" => Ok(TextureFormat::Bc4),
+        b"BC4S" => Ok(TextureFormat::Bc4Signed),
+        b"ATI2" | b"BC5U" => Ok(TextureFormat::Bc5),
+        b"BC5S" => Ok(TextureFormat::Bc5Signed),
+        _ => Err(format!(
+            "[FerrousGl Error] Unsupported DDS format {}.",
+            String::from_utf8_lossy(four_cc)
+        )),
+    }
+}
+
+fn format_from_dxgi(dxgi_format: u32) -> Result<TextureFormat, String> {
+    match dxgi_format {
+        70 | 71 => Ok(TextureFormat::Bc1),
+        72 => Ok(TextureFormat::Bc1Srgb),
+        73 | 74 => Ok(TextureFormat::Bc2),
+        75 => Ok(TextureFormat::Bc2Srgb),
+        76 | 77 => Ok(TextureFormat::Bc3),
+        78 => Ok(TextureFormat::Bc3Srgb),
+        79 | 80 => Ok(TextureFormat::Bc4),
+        81 => Ok(TextureFormat::Bc4Signed),
+        82 | 83 => Ok(TextureFormat::Bc5),
+        84 => Ok(TextureFormat::Bc5Signed),
+        94 | 95 => Ok(TextureFormat::Bc6H),
+        96 => Ok(TextureFormat::Bc6HSigned),
+        97 | 98 => Ok(TextureFormat::Bc7),
+        99 => Ok(TextureFormat::Bc7Srgb),
+        _ => Err(format!("[FerrousGl Error] Unsupported DXGI format {} in DDS file.", dxgi_format)),
+    }
+}
+
+fn format_from_vk(vk_format: u32) -> Result<TextureFormat, String> {
+    match vk_format {
+        131 | 133 => Ok(TextureFormat::Bc1),
+        132 | 134 => Ok(TextureFormat::Bc1Srgb),
+        135 => Ok(TextureFormat::Bc2),
+        136 => Ok(TextureFormat::Bc2Srgb),
+        137 => Ok(TextureFormat::Bc3),
+        138 => Ok(TextureFormat::Bc3Srgb),
+        139 => Ok(TextureFormat::Bc4),
+        140 => Ok(TextureFormat::Bc4Signed),
+        141 => Ok(TextureFormat::Bc5),
+        142 => Ok(TextureFormat::Bc5Signed),
+        143 => Ok(TextureFormat::Bc6H),
+        144 => Ok(TextureFormat::Bc6HSigned),
+        145 => Ok(TextureFormat::Bc7),
+        146 => Ok(TextureFormat::Bc7Srgb),
+        _ => Err(format!(
+            "[FerrousGl Error] Unsupported KTX2 format {}, only BC1 to BC7 compressed textures can be loaded.",
+            vk_format
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dds_header(width: u32, height: u32, mip_count: u32, four_cc: &[u8; 4]) -> Vec<u8> {
+        let mut bytes = vec![0; DDS_HEADER_SIZE];
+        bytes[0..4].copy_from_slice(DDS_MAGIC);
+        bytes[8..12].copy_from_slice(&DDSD_MIPMAPCOUNT.to_le_bytes());
+        bytes[12..16].copy_from_slice(&height.to_le_bytes());
+        bytes[16..20].copy_from_slice(&width.to_le_bytes());
+        bytes[28..32].copy_from_slice(&mip_count.to_le_bytes());
+        bytes[80..84].copy_from_slice(&DDPF_FOURCC.to_le_bytes());
+        bytes[84..88].copy_from_slice(four_cc);
+        bytes
+    }
+
+    fn ktx2_file(width: u32, height: u32, level_count: u32, levels: &[(u64, u64)], data_size: usize) -> Vec<u8> {
+        let mut bytes = vec![0; KTX2_HEADER_SIZE];
+        bytes[0..12].copy_from_slice(&KTX2_IDENTIFIER);
+        bytes[12..16].copy_from_slice(&131u32.to_le_bytes());
+        bytes[20..24].copy_from_slice(&width.to_le_bytes());
+        bytes[24..28].copy_from_slice(&height.to_le_bytes());
+        bytes[36..40].copy_from_slice(&1u32.to_le_bytes());
+        bytes[40..44].copy_from_slice(&level_count.to_le_bytes());
+        for &(offset, length) in levels {
+            bytes.extend_from_slice(&offset.to_le_bytes());
+            bytes.extend_from_slice(&length.to_le_bytes());
+            bytes.extend_from_slice(&length.to_le_bytes());
+        }
+        bytes.resize(bytes.len() + data_size, 0xAB);
+        bytes
+    }
+
+    #[test]
+    fn dds_parses_mip_chain() {
+        let mut bytes = dds_header(8, 8, 4, b"DXT5");
+        bytes.resize(DDS_HEADER_SIZE + 64 + 16 + 16 + 16, 1);
+
+        let image = CompressedImage::from_dds_bytes(&bytes).unwrap();
+        assert_eq!((image.width, image.height), (8, 8));
+        assert_eq!(image.format, TextureFormat::Bc3);
+        let sizes: Vec<usize> = image.levels.iter().map(Vec::len).collect();
+        assert_eq!(sizes, [64, 16, 16, 16]);
+    }
+
+    #[test]
+    fn dds_rejects_truncated_files() {
+        let mut bytes = dds_header(8, 8, 1, b"DXT1");
+        bytes.resize(DDS_HEADER_SIZE + 31, 0);
+        assert!(CompressedImage::from_dds_bytes(&bytes).is_err());
+        assert!(CompressedImage::from_dds_bytes(&bytes[..100]).is_err());
+    }
+
+    #[test]
+    fn dds_clamps_oversized_level_counts() {
+        let mut bytes = dds_header(4, 4, u32::MAX, b"DXT1");
+        bytes.resize(DDS_HEADER_SIZE + 8 * 3, 0);
+
+        let image = CompressedImage::from_dds_bytes(&bytes).unwrap();
+        assert_eq!(image.levels.len(), mip_level_count(4, 4) as usize);
+    }
+
+    #[test]
+    fn dds_rejects_huge_dimensions() {
+        let mut bytes = dds_header(i32::MAX as u32, i32::MAX as u32, 1, b"DXT1");
+        bytes.resize(DDS_HEADER_SIZE + 64, 0);
+        assert!(CompressedImage::from_dds_bytes(&bytes).is_err());
+
+        let bytes = dds_header(0, 4, 1, b"DXT1");
+        assert!(CompressedImage::from_dds_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn ktx2_parses_levels() {
+        let data_start = (KTX2_HEADER_SIZE + 2 * 24) as u64;
+        let bytes = ktx2_file(8, 4, 2, &[(data_start, 16), (data_start + 16, 8)], 24);
+
+        let image = CompressedImage::from_ktx2_bytes(&bytes).unwrap();
+        assert_eq!((image.width, image.height), (8, 4));
+        assert_eq!(image.format, TextureFormat::Bc1);
+        let sizes: Vec<usize> = image.levels.iter().map(Vec::len).collect();
+        assert_eq!(sizes, [16, 8]);
+    }
+
+    #[test]
+    fn ktx2_rejects_truncated_files() {
+        let data_start = (KTX2_HEADER_SIZE + 24) as u64;
+        let bytes = ktx2_file(4, 4, 1, &[(data_start, 8)], 4);
+        assert!(CompressedImage::from_ktx2_bytes(&bytes).is_err());
+
+        let bytes = ktx2_file(4, 4, 1, &[(u64::MAX, 8)], 8);
+        assert!(CompressedImage::from_ktx2_bytes(&bytes).is_err());
+
+        let bytes = ktx2_file(4, 4, 1, &[(data_start, u64::MAX)], 8);
+        assert!(CompressedImage::from_ktx2_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn ktx2_rejects_level_index_past_end_of_file() {
+        let data_start = (KTX2_HEADER_SIZE + 24) as u64;
+        let bytes = ktx2_file(1024, 1024, 8, &[(data_start, 8)], 8);
+        assert!(CompressedImage::from_ktx2_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn ktx2_clamps_oversized_level_counts() {
+        let data_start = (KTX2_HEADER_SIZE + 24) as u64;
+        let bytes = ktx2_file(1, 1, u32::MAX, &[(data_start, 8)], 8);
+
+        let image = CompressedImage::from_ktx2_bytes(&bytes).unwrap();
+        assert_eq!(image.levels.len(), 1);
+    }
+}
@@ -0,0 +1,358 @@
+extern crate gl;
+extern crate image;
+
+use gl::types::*;
+use glam::{Mat4, Vec3};
+use image::DynamicImage;
+use std::path::Path;
+use std::ptr;
+
+use crate::texture::{image_to_bytes, set_texture_filtering, upload_2d, with_bound_texture, FilterMode, MipmapType, TextureFormat};
+use crate::{Mesh, Shader, Texture};
+
+const EQUIRECTANGULAR_VERTEX_SHADER: &str = r#"
+#version 330 core
+layout (location = 0) in vec3 aPos;
+
+uniform mat4 projection;
+uniform mat4 view;
+
+out vec3 localPos;
+
+void main() {
+    localPos = aPos;
+    gl_Position = projection * view * vec4(aPos, 1.0);
+}
+"#;
+
+const EQUIRECTANGULAR_FRAGMENT_SHADER: &str = r#"
+#version 330 core
+in vec3 localPos;
+
+uniform sampler2D equirectangularMap;
+
+out vec4 FragColor;
+
+const vec2 invAtan = vec2(0.1591, 0.3183);
+
+void main() {
+    vec3 direction = normalize(localPos);
+    // Images are uploaded top row first, so the top of the sphere is at v = 0
+    vec2 uv = vec2(atan(direction.z, direction.x) * invAtan.x + 0.5, 0.5 - asin(direction.y) * invAtan.y);
+    FragColor = vec4(texture(equirectangularMap, uv).rgb, 1.0);
+}
+"#;
+
+/// Represents an OpenGL cubemap texture, made of six square faces. Cubemaps are sampled with a direction
+/// (`samplerCube` in GLSL), which makes them useful for skyboxes, reflections and point light shadows.
+/// Faces are always ordered +X, -X, +Y, -Y, +Z, -Z (right, left, top, bottom, front, back).
+/// [`crate::GlWindow`] enables `GL_TEXTURE_CUBE_MAP_SEAMLESS`, so sampling filters across face edges.
+pub struct CubemapTexture {
+    pub(crate) id: GLuint,
+    pub(crate) size: u32,
+    pub(crate) format: TextureFormat,
+}
+
+impl CubemapTexture {
+    /// Creates a new cubemap from six image files, ordered +X, -X, +Y, -Y, +Z, -Z.
+    pub fn new_from_files(paths: [&Path; 6]) -> Result<Self, String> {
+        let mut images = Vec::with_capacity(6);
+        for path in paths {
+            images.push(image::open(path).map_err(|e| e.to_string())?);
+        }
+
+        Self::from_images([
+            &images[0], &images[1], &images[2], &images[3], &images[4], &images[5],
+        ])
+    }
+
+    /// Creates a new cubemap from a single image file laid out as a cross or a strip (see [`CubemapTexture::from_layout_image`]).
+    pub fn new_from_layout_file(path: &Path) -> Result<Self, String> {
+        let img = image::open(path).map_err(|e| e.to_string())?;
+        Self::from_layout_image(&img)
+    }
+
+    /// Creates a new cubemap from an equirectangular (latitude-longitude) image file, such as a Radiance `.hdr`
+    /// panorama. The conversion runs on the GPU, each resulting face is `face_size` pixels wide.
+    pub fn new_from_equirectangular_file(path: &Path, face_size: u32) -> Result<Self, String> {
+        let texture = Texture::new_from_file(path)?;
+        Self::from_equirectangular(&texture, face_size)
+    }
+
+    /// Creates a new cubemap from six in-memory images, ordered +X, -X, +Y, -Y, +Z, -Z.
+    /// All faces have to be square and the same size.
+    pub fn from_images(faces: [&DynamicImage; 6]) -> Result<Self, String> {
+        let size = faces[0].width();
+        if faces.iter().any(|face| face.width() != size || face.height() != size) {
+            return Err("[FerrousGl Error] All cubemap faces have to be square and the same size!".to_string());
+        }
+
+        let format = TextureFormat::for_image(faces[0]);
+        let cubemap = Self::new_empty(size, format)?;
+
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, cubemap.id);
+
+            for (i, face) in faces.iter().enumerate() {
+                let data = image_to_bytes(face, format)?;
+                upload_2d(
+                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + i as GLenum,
+                    0,
+                    format,
+                    size,
+                    size,
+                    data.as_ptr() as *const GLvoid,
+                );
+            }
+
+            // Integer formats can not be filtered, so they keep the nearest filter without mipmaps
+            if !format.is_integer() {
+                gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MIN_FILTER, gl::LINEAR_MIPMAP_LINEAR as i32);
+                gl::GenerateMipmap(gl::TEXTURE_CUBE_MAP);
+            }
+
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+        }
+
+        Ok(cubemap)
+    }
+
+    /// Creates a new cubemap from a single image containing all six faces. The layout is detected from the
+    /// image's aspect ratio:
+    /// - 4:3, a horizontal cross (`-X +Z +X -Z` in the middle row, `+Y` above and `-Y` below `+Z`)
+    /// - 3:4, a vertical cross (`-X +Z +X` in the second row, `+Y` above, `-Y` and an upside-down `-Z` below `+Z`)
+    /// - 6:1 or 1:6, a strip of faces ordered +X, -X, +Y, -Y, +Z, -Z
+    pub fn from_layout_image(img: &DynamicImage) -> Result<Self, String> {
+        let (width, height) = (img.width(), img.height());
+
+        // Face positions in the layout grid, in units of faces, ordered +X, -X, +Y, -Y, +Z, -Z
+        let (size, cells): (u32, [(u32, u32); 6]) = if width * 3 == height * 4 {
+            (width / 4, [(2, 1), (0, 1), (1, 0), (1, 2), (1, 1), (3, 1)])
+        } else if width * 4 == height * 3 {
+            (width / 3, [(2, 1), (0, 1), (1, 0), (1, 2), (1, 1), (1, 3)])
+        } else if width == height * 6 {
+            (height, [(0, 0), (1, 0), (2, 0), (3, 0), (4, 0), (5, 0)])
+        } else if height == width * 6 {
+            (width, [(0, 0), (0, 1), (0, 2), (0, 3), (0, 4), (0, 5)])
+        } else {
+            return Err(format!(
+                "[FerrousGl Error] Can not detect the cubemap layout of a {}x{} image. Expected a 4:3 or 3:4 cross, or a 6:1 or 1:6 strip.",
+                width, height
+            ));
+        };
+
+        let mut faces: Vec<DynamicImage> = cells
+            .iter()
+            .map(|&(column, row)| img.crop_imm(column * size, row * size, size, size))
+            .collect();
+
+        // In a vertical cross the back face is reached by folding downwards, which turns it upside down
+        if width * 4 == height * 3 {
+            faces[5] = faces[5].rotate180();
+        }
+
+        Self::from_images([&faces[0], &faces[1], &faces[2], &faces[3], &faces[4], &faces[5]])
+    }
+
+    /// Creates a new cubemap from an equirectangular (latitude-longitude) texture by rendering it onto each face
+    /// on the GPU. The cubemap uses `TextureFormat::Rgba16F`, so HDR panoramas keep their range.
+    pub fn from_equirectangular(texture: &Texture, face_size: u32) -> Result<Self, String> {
+        let cubemap = Self::new_empty(face_size, TextureFormat::Rgba16F)?;
+
+        let shader = Shader::new_from_source(EQUIRECTANGULAR_VERTEX_SHADER, EQUIRECTANGULAR_FRAGMENT_SHADER);
+        let mesh = cube_mesh();
+        let projection = Mat4::perspective_rh_gl(90.0f32.to_radians(), 1.0, 0.1, 10.0);
+        let views = Self::face_views(Vec3::ZERO);
+
+        unsafe {
+            let mut previous_framebuffer = 0;
+            let mut previous_viewport = [0; 4];
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous_framebuffer);
+            gl::GetIntegerv(gl::VIEWPORT, previous_viewport.as_mut_ptr());
+            let depth_test_enabled = gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE;
+
+            let mut framebuffer_id = 0;
+            gl::GenFramebuffers(1, &mut framebuffer_id);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer_id);
+            gl::Viewport(0, 0, face_size as i32, face_size as i32);
+            gl::Disable(gl::DEPTH_TEST);
+
+            shader.bind_program();
+            texture.bind(0);
+            shader.set_uniform_texture("equirectangularMap", 0);
+            shader.set_uniform_matrix_4fv("projection", projection.to_cols_array().as_ref());
+
+            for (i, view) in views.iter().enumerate() {
+                gl::FramebufferTexture2D(
+                    gl::FRAMEBUFFER,
+                    gl::COLOR_ATTACHMENT0,
+                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + i as GLenum,
+                    cubemap.id,
+                    0,
+                );
+
+                if gl::CheckFramebufferStatus(gl::FRAMEBUFFER) != gl::FRAMEBUFFER_COMPLETE {
+                    gl::BindFramebuffer(gl::FRAMEBUFFER, previous_framebuffer as GLuint);
+                    gl::DeleteFramebuffers(1, &framebuffer_id);
+                    return Err("[FerrousGl Error] Framebuffer is not complete!".to_string());
+                }
+
+                gl::Clear(gl::COLOR_BUFFER_BIT);
+                shader.set_uniform_matrix_4fv("view", view.to_cols_array().as_ref());
+
+                mesh.bind();
+                gl::DrawElements(gl::TRIANGLES, mesh.indices_length as i32, gl::UNSIGNED_INT, ptr::null());
+                mesh.unbind();
+            }
+
+            texture.unbind();
+            shader.unbind_program();
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, previous_framebuffer as GLuint);
+            gl::DeleteFramebuffers(1, &framebuffer_id);
+            gl::Viewport(
+                previous_viewport[0],
+                previous_viewport[1],
+                previous_viewport[2],
+                previous_viewport[3],
+            );
+            if depth_test_enabled {
+                gl::Enable(gl::DEPTH_TEST);
+            }
+        }
+
+        cubemap.generate_mipmaps();
+        cubemap.set_mipmap_and_filtering(MipmapType::Linear, FilterMode::Linear);
+
+        Ok(cubemap)
+    }
+
+    /// Creates an empty cubemap with faces of the given size and texture format, for example to render reflection
+    /// probes or point light shadows into.
+    pub fn new_empty(size: u32, format: TextureFormat) -> Result<Self, String> {
+        let mut texture_id = 0;
+
+        unsafe {
+            gl::GenTextures(1, &mut texture_id);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, texture_id);
+
+            for i in 0..6 {
+                upload_2d(gl::TEXTURE_CUBE_MAP_POSITIVE_X + i, 0, format, size, size, ptr::null());
+            }
+
+            let filter = format.default_filter();
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MIN_FILTER, filter as i32);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MAG_FILTER, filter as i32);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_R, gl::CLAMP_TO_EDGE as i32);
+
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+        }
+
+        Ok(CubemapTexture {
+            id: texture_id,
+            size,
+            format,
+        })
+    }
+
+    /// Returns the view matrices looking from `position` through each cubemap face, ordered +X, -X, +Y, -Y, +Z, -Z.
+    /// Combined with a 90 degree perspective projection with an aspect ratio of 1, these render a scene into a cubemap.
+    pub fn face_views(position: Vec3) -> [Mat4; 6] {
+        [
+            Mat4::look_at_rh(position, position + Vec3::X, Vec3::NEG_Y),
+            Mat4::look_at_rh(position, position + Vec3::NEG_X, Vec3::NEG_Y),
+            Mat4::look_at_rh(position, position + Vec3::Y, Vec3::Z),
+            Mat4::look_at_rh(position, position + Vec3::NEG_Y, Vec3::NEG_Z),
+            Mat4::look_at_rh(position, position + Vec3::Z, Vec3::NEG_Y),
+            Mat4::look_at_rh(position, position + Vec3::NEG_Z, Vec3::NEG_Y),
+        ]
+    }
+
+    /// Binds the cubemap to a specific texture unit which can be used to set a uniform texture.
+    pub fn bind(&self, texture_unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + texture_unit);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.id);
+        }
+    }
+
+    /// Unbinds the cubemap.
+    pub fn unbind(&self) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+        }
+    }
+
+    /// Sets the preferred Texture Mipmap Type or Texture Filtering Mode such as None, Linear and Nearest.
+    pub fn set_mipmap_and_filtering(&self, mipmap_type: MipmapType, base_filter: FilterMode) {
+        set_texture_filtering(gl::TEXTURE_CUBE_MAP, self.id, mipmap_type, base_filter);
+    }
+
+    /// Generates mipmaps for all faces from their first level. Does nothing for integer cubemaps.
+    pub fn generate_mipmaps(&self) {
+        if self.format.is_integer() {
+            return;
+        }
+
+        self.with_bound(|| unsafe {
+            gl::GenerateMipmap(gl::TEXTURE_CUBE_MAP);
+        });
+    }
+
+    /// Returns the width and height of each face.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Returns the format the faces are stored in on the GPU.
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    /// Binds the cubemap to the active texture unit, runs `f` and then restores the previously bound cubemap.
+    fn with_bound<F: FnOnce()>(&self, f: F) {
+        with_bound_texture(gl::TEXTURE_CUBE_MAP, self.id, f);
+    }
+}
+
+impl Drop for CubemapTexture {
+    /// Cleans up the cubemap when it goes out of scope.
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
+    }
+}
+
+/// Creates a cube from -1 to 1 on every axis, with only a position attribute at location 0.
+pub(crate) fn cube_mesh() -> Mesh {
+    let vertices = [
+        -1.0, -1.0, -1.0,
+         1.0, -1.0, -1.0,
+         1.0,  1.0, -1.0,
+        -1.0,  1.0, -1.0,
+        -1.0, -1.0,  1.0,
+         1.0, -1.0,  1.0,
+         1.0,  1.0,  1.0,
+        -1.0,  1.0,  1.0,
+    ];
+
+    let indices = [
+        0, 1, 2,  2, 3, 0,  // back
+        4, 6, 5,  6, 4, 7,  // front
+        0, 3, 7,  7, 4, 0,  // left
+        1, 5, 6,  6, 2, 1,  // right
+        0, 4, 5,  5, 1, 0,  // bottom
+        3, 2, 6,  6, 7, 3,  // top
+    ];
+
+    let mut mesh = Mesh::new();
+    mesh.update_vertices(&vertices);
+    mesh.update_indices(&indices);
+    mesh.add_vertex_attributes(&[
+        (0, 3, gl::FLOAT, false), // position
+    ]);
+    mesh
+}
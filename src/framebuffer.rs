@@ -0,0 +1,179 @@
+extern crate gl;
+
+use gl::types::*;
+
+use crate::readback::check_rect;
+use crate::texture::FilterMode;
+use crate::RenderTexture;
+
+/// A framebuffer that can be copied from or to, either the window's or a render texture's.
+#[derive(Clone, Copy)]
+pub enum Framebuffer<'a> {
+    /// The window's framebuffer.
+    Default,
+    /// A render texture. Multisampled render textures are read from and written to after resolving,
+    /// so call [`RenderTexture::resolve`] before using one as a source.
+    RenderTexture(&'a RenderTexture),
+}
+
+impl Framebuffer<'_> {
+    fn id(&self) -> GLuint {
+        match self {
+            Framebuffer::Default => 0,
+            Framebuffer::RenderTexture(render_texture) => render_texture.framebuffer_id,
+        }
+    }
+
+    /// Returns the size in pixels, for the window that of the current context's framebuffer.
+    fn size(&self) -> (u32, u32) {
+        match self {
+            Framebuffer::Default => {
+                let (mut width, mut height) = (0, 0);
+                unsafe {
+                    let window = glfw::ffi::glfwGetCurrentContext();
+                    if !window.is_null() {
+                        glfw::ffi::glfwGetFramebufferSize(window, &mut width, &mut height);
+                    }
+                }
+                (width.max(0) as u32, height.max(0) as u32)
+            }
+            Framebuffer::RenderTexture(render_texture) => (render_texture.width(), render_texture.height()),
+        }
+    }
+}
+
+/// A rectangle in pixels, starting at the bottom left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    /// Creates a new rectangle.
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Rect { x, y, width, height }
+    }
+
+    /// Creates a rectangle covering a whole texture or framebuffer of the given size.
+    pub fn from_size(width: u32, height: u32) -> Self {
+        Rect::new(0, 0, width, height)
+    }
+}
+
+/// Which buffers of a framebuffer are copied by [`blit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlitBuffers {
+    pub color: bool,
+    pub depth: bool,
+    pub stencil: bool,
+}
+
+impl BlitBuffers {
+    /// Only the color buffer.
+    pub const COLOR: Self = BlitBuffers { color: true, depth: false, stencil: false };
+    /// Only the depth buffer.
+    pub const DEPTH: Self = BlitBuffers { color: false, depth: true, stencil: false };
+    /// The depth and stencil buffers.
+    pub const DEPTH_STENCIL: Self = BlitBuffers { color: false, depth: true, stencil: true };
+    /// The color, depth and stencil buffers.
+    pub const ALL: Self = BlitBuffers { color: true, depth: true, stencil: true };
+
+    fn mask(&self) -> GLbitfield {
+        let mut mask = 0;
+        if self.color {
+            mask |= gl::COLOR_BUFFER_BIT;
+        }
+        if self.depth {
+            mask |= gl::DEPTH_BUFFER_BIT;
+        }
+        if self.stencil {
+            mask |= gl::STENCIL_BUFFER_BIT;
+        }
+        mask
+    }
+}
+
+/// Copies a rectangle of one framebuffer into a rectangle of another with `glBlitFramebuffer`, scaling it if the
+/// sizes differ. Color is read from the first color texture of a render texture and written to all of them.
+/// Depth and stencil can only be copied between matching formats and with `FilterMode::Nearest`.
+pub fn blit(
+    src: Framebuffer,
+    dst: Framebuffer,
+    src_rect: Rect,
+    dst_rect: Rect,
+    filter: FilterMode,
+    buffers: BlitBuffers,
+) -> Result<(), String> {
+    if (buffers.depth || buffers.stencil) && filter != FilterMode::Nearest {
+        return Err("[FerrousGl Error] Depth and stencil buffers can only be blitted with FilterMode::Nearest.".to_string());
+    }
+
+    check_blit_rect(src_rect, src, "source")?;
+    check_blit_rect(dst_rect, dst, "destination")?;
+
+    let mask = buffers.mask();
+    if mask == 0 {
+        return Ok(());
+    }
+
+    unsafe {
+        let mut previous_read = 0;
+        let mut previous_draw = 0;
+        gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut previous_read);
+        gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut previous_draw);
+
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, src.id());
+        gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, dst.id());
+
+        gl::BlitFramebuffer(
+            src_rect.x as i32,
+            src_rect.y as i32,
+            (src_rect.x + src_rect.width) as i32,
+            (src_rect.y + src_rect.height) as i32,
+            dst_rect.x as i32,
+            dst_rect.y as i32,
+            (dst_rect.x + dst_rect.width) as i32,
+            (dst_rect.y + dst_rect.height) as i32,
+            mask,
+            GLenum::from(filter),
+        );
+
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, previous_read as GLuint);
+        gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, previous_draw as GLuint);
+    }
+
+    Ok(())
+}
+
+/// Checks that a blit rectangle is not empty and lies inside its framebuffer, which also keeps its corners in `i32` range.
+fn check_blit_rect(rect: Rect, framebuffer: Framebuffer, name: &str) -> Result<(), String> {
+    if rect.width == 0 || rect.height == 0 {
+        return Err(format!(
+            "[FerrousGl Error] The blit {} rectangle {}x{} at ({}, {}) is empty.",
+            name, rect.width, rect.height, rect.x, rect.y
+        ));
+    }
+
+    let (width, height) = framebuffer.size();
+    check_rect(rect, width, height)
+}
+
+/// Runs `f` and then restores the read and draw framebuffer bindings that were active before,
+/// so creating framebuffers doesn't silently switch rendering back to the window.
+pub(crate) fn preserve_framebuffer_bindings<R, F: FnOnce() -> R>(f: F) -> R {
+    unsafe {
+        let mut previous_read = 0;
+        let mut previous_draw = 0;
+        gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut previous_read);
+        gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut previous_draw);
+
+        let result = f();
+
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, previous_read as GLuint);
+        gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, previous_draw as GLuint);
+        result
+    }
+}
@@ -2,7 +2,25 @@ pub mod window;
 pub mod mesh;
 pub mod shader;
 pub mod texture;
+pub mod mipmap;
 pub mod render_texture;
+pub mod framebuffer;
+pub mod readback;
+pub mod sampler;
+pub mod cubemap;
+pub mod texture_array;
+pub mod texture_3d;
+pub mod compressed_texture;
+pub mod texture_atlas;
+pub mod texture_loader;
+pub mod skybox;
+pub mod post_process;
+pub mod camera;
+pub mod scene;
+pub mod material;
+pub mod lighting;
+pub mod shadow_map;
+pub mod sprite_batch;
 
 pub use window::GlWindow;
 pub use window::RenderingType;
@@ -11,9 +29,62 @@ pub use window::BlendMode;
 pub use window::BlendFactor;
 pub use window::WindowKey;
 pub use window::WindowConfig;
+pub use window::StencilState;
+pub use window::StencilFaceState;
+pub use window::StencilFunc;
+pub use window::StencilOp;
 pub use mesh::Mesh;
 pub use shader::Shader;
 pub use texture::Texture;
 pub use texture::MipmapType;
 pub use texture::FilterMode;
-pub use render_texture::RenderTexture;
\ No newline at end of file
+pub use texture::TextureFormat;
+pub use texture::WrapMode;
+pub use texture::TextureOptions;
+pub use texture::MipmapGeneration;
+pub use render_texture::RenderTexture;
+pub use render_texture::RenderTextureConfig;
+pub use framebuffer::blit;
+pub use framebuffer::Framebuffer;
+pub use framebuffer::Rect;
+pub use framebuffer::BlitBuffers;
+pub use readback::PixelFormat;
+pub use readback::PixelComponent;
+pub use readback::PixelReadback;
+pub use sampler::Sampler;
+pub use cubemap::CubemapTexture;
+pub use skybox::Skybox;
+pub use post_process::PostProcessStack;
+pub use post_process::PostEffect;
+pub use post_process::Tonemapper;
+pub use texture_array::TextureArray;
+pub use texture_3d::Texture3D;
+pub use compressed_texture::CompressedImage;
+pub use texture_atlas::TextureAtlas;
+pub use texture_atlas::TextureAtlasBuilder;
+pub use texture_loader::TextureLoader;
+pub use texture_loader::TextureHandle;
+pub use texture_loader::TextureLoadState;
+pub use camera::Camera;
+pub use camera::Projection;
+pub use camera::OrbitController;
+pub use camera::FlyController;
+pub use scene::Scene;
+pub use scene::Transform;
+pub use scene::NodeId;
+pub use material::Material;
+pub use material::MaterialTexture;
+pub use material::UniformValue;
+pub use lighting::LitShaderConfig;
+pub use lighting::LightingModel;
+pub use lighting::Lights;
+pub use lighting::DirectionalLight;
+pub use lighting::PointLight;
+pub use lighting::SpotLight;
+pub use shadow_map::CascadedShadowMap;
+pub use shadow_map::CascadedShadowConfig;
+pub use shadow_map::SpotShadowMap;
+pub use shadow_map::PointShadowMap;
+pub use sprite_batch::SpriteBatch;
+pub use sprite_batch::Sprite;
+pub use sprite_batch::SpriteSortMode;
\ No newline at end of file
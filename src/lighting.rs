@@ -0,0 +1,540 @@
+extern crate glam;
+
+use glam::{Vec3, Vec4};
+use std::rc::Rc;
+
+use crate::{Material, Shader};
+
+const LIT_VERTEX_SHADER: &str = r#"
+layout (location = 0) in vec3 aPos;
+layout (location = 1) in vec3 aNormal;
+layout (location = 2) in vec2 aTexCoord;
+layout (location = 3) in vec4 aTangent;
+
+uniform mat4 model;
+uniform mat4 view;
+uniform mat4 projection;
+
+out vec3 worldPosition;
+out vec3 worldNormal;
+out vec2 texCoord;
+#ifdef NORMAL_MAPPING
+out vec4 worldTangent;
+#endif
+
+void main() {
+    vec4 world = model * vec4(aPos, 1.0);
+    worldPosition = world.xyz;
+    worldNormal = normalize(transpose(inverse(mat3(model))) * aNormal);
+#ifdef NORMAL_MAPPING
+    worldTangent = vec4(normalize(mat3(model) * aTangent.xyz), aTangent.w);
+#endif
+    texCoord = aTexCoord;
+    gl_Position = projection * view * world;
+}
+"#;
+
+const LIT_FRAGMENT_SHADER: &str = r#"
+in vec3 worldPosition;
+in vec3 worldNormal;
+in vec2 texCoord;
+#ifdef NORMAL_MAPPING
+in vec4 worldTangent;
+#endif
+
+out vec4 FragColor;
+
+struct DirectionalLight {
+    vec3 direction;
+    vec3 color;
+    float intensity;
+};
+
+struct PointLight {
+    vec3 position;
+    vec3 color;
+    float intensity;
+    float range;
+};
+
+struct SpotLight {
+    vec3 position;
+    vec3 direction;
+    vec3 color;
+    float intensity;
+    float range;
+    float innerCutoff;
+    float outerCutoff;
+};
+
+#if MAX_DIRECTIONAL_LIGHTS > 0
+uniform DirectionalLight directionalLights[MAX_DIRECTIONAL_LIGHTS];
+uniform int directionalLightCount;
+#endif
+#if MAX_POINT_LIGHTS > 0
+uniform PointLight pointLights[MAX_POINT_LIGHTS];
+uniform int pointLightCount;
+#endif
+#if MAX_SPOT_LIGHTS > 0
+uniform SpotLight spotLights[MAX_SPOT_LIGHTS];
+uniform int spotLightCount;
+#endif
+
+uniform vec3 viewPos;
+uniform vec3 ambientColor;
+uniform vec4 baseColor;
+uniform vec3 emissive;
+#ifdef BASE_COLOR_TEXTURE
+uniform sampler2D baseColorTexture;
+#endif
+#ifdef NORMAL_MAPPING
+uniform sampler2D normalTexture;
+uniform float normalScale;
+#endif
+#ifdef EMISSIVE_TEXTURE
+uniform sampler2D emissiveTexture;
+#endif
+
+#ifdef PBR
+uniform float metallic;
+uniform float roughness;
+#ifdef METALLIC_ROUGHNESS_TEXTURE
+uniform sampler2D metallicRoughnessTexture;
+#endif
+#else
+uniform vec3 specularColor;
+uniform float shininess;
+#endif
+
+const float PI = 3.14159265359;
+
+struct Surface {
+    vec3 albedo;
+    vec3 normal;
+    vec3 viewDir;
+#ifdef PBR
+    float metallic;
+    float roughness;
+    vec3 f0;
+#endif
+};
+
+#ifdef PBR
+float distributionGgx(float nDotH, float roughness) {
+    float a = roughness * roughness;
+    float a2 = a * a;
+    float d = nDotH * nDotH * (a2 - 1.0) + 1.0;
+    return a2 / (PI * d * d);
+}
+
+float geometrySmith(float nDotV, float nDotL, float roughness) {
+    float r = roughness + 1.0;
+    float k = r * r / 8.0;
+    return (nDotV / (nDotV * (1.0 - k) + k)) * (nDotL / (nDotL * (1.0 - k) + k));
+}
+
+vec3 fresnelSchlick(float cosTheta, vec3 f0) {
+    return f0 + (1.0 - f0) * pow(clamp(1.0 - cosTheta, 0.0, 1.0), 5.0);
+}
+
+vec3 shade(Surface surface, vec3 lightDir, vec3 radiance) {
+    float nDotL = max(dot(surface.normal, lightDir), 0.0);
+    if (nDotL <= 0.0) {
+        return vec3(0.0);
+    }
+
+    vec3 halfway = normalize(lightDir + surface.viewDir);
+    float nDotV = max(dot(surface.normal, surface.viewDir), 0.0001);
+    float nDotH = max(dot(surface.normal, halfway), 0.0);
+
+    float d = distributionGgx(nDotH, surface.roughness);
+    float g = geometrySmith(nDotV, nDotL, surface.roughness);
+    vec3 f = fresnelSchlick(max(dot(halfway, surface.viewDir), 0.0), surface.f0);
+
+    vec3 specular = d * g * f / (4.0 * nDotV * nDotL + 0.0001);
+    vec3 diffuse = (1.0 - f) * (1.0 - surface.metallic) * surface.albedo / PI;
+    return (diffuse + specular) * radiance * nDotL;
+}
+#else
+vec3 shade(Surface surface, vec3 lightDir, vec3 radiance) {
+    float diffuse = max(dot(surface.normal, lightDir), 0.0);
+    if (diffuse <= 0.0) {
+        return vec3(0.0);
+    }
+
+    vec3 halfway = normalize(lightDir + surface.viewDir);
+    float specular = pow(max(dot(surface.normal, halfway), 0.0), shininess);
+    return (surface.albedo * diffuse + specularColor * specular) * radiance;
+}
+#endif
+
+// Inverse square falloff that smoothly reaches zero at the light's range
+float attenuation(float lightDistance, float range) {
+    float ratio = lightDistance / range;
+    float window = clamp(1.0 - ratio * ratio * ratio * ratio, 0.0, 1.0);
+    return window * window / (lightDistance * lightDistance + 1.0);
+}
+
+void main() {
+    vec4 albedo = baseColor;
+#ifdef BASE_COLOR_TEXTURE
+    albedo *= texture(baseColorTexture, texCoord);
+#endif
+
+    vec3 normal = normalize(worldNormal);
+#ifdef NORMAL_MAPPING
+    vec3 tangent = normalize(worldTangent.xyz - normal * dot(normal, worldTangent.xyz));
+    vec3 bitangent = cross(normal, tangent) * worldTangent.w;
+    vec3 mapped = texture(normalTexture, texCoord).xyz * 2.0 - 1.0;
+    mapped.xy *= normalScale;
+    normal = normalize(mat3(tangent, bitangent, normal) * mapped);
+#endif
+
+    Surface surface;
+    surface.albedo = albedo.rgb;
+    surface.normal = normal;
+    surface.viewDir = normalize(viewPos - worldPosition);
+#ifdef PBR
+    surface.metallic = metallic;
+    surface.roughness = roughness;
+#ifdef METALLIC_ROUGHNESS_TEXTURE
+    vec4 metallicRoughness = texture(metallicRoughnessTexture, texCoord);
+    surface.roughness *= metallicRoughness.g;
+    surface.metallic *= metallicRoughness.b;
+#endif
+    surface.roughness = clamp(surface.roughness, 0.04, 1.0);
+    surface.f0 = mix(vec3(0.04), surface.albedo, surface.metallic);
+#endif
+
+    vec3 color = ambientColor * surface.albedo;
+
+#if MAX_DIRECTIONAL_LIGHTS > 0
+    for (int i = 0; i < min(directionalLightCount, MAX_DIRECTIONAL_LIGHTS); i++) {
+        DirectionalLight light = directionalLights[i];
+        color += shade(surface, normalize(-light.direction), light.color * light.intensity);
+    }
+#endif
+
+#if MAX_POINT_LIGHTS > 0
+    for (int i = 0; i < min(pointLightCount, MAX_POINT_LIGHTS); i++) {
+        PointLight light = pointLights[i];
+        vec3 toLight = light.position - worldPosition;
+        float lightDistance = length(toLight);
+        vec3 radiance = light.color * light.intensity * attenuation(lightDistance, light.range);
+        color += shade(surface, toLight / lightDistance, radiance);
+    }
+#endif
+
+#if MAX_SPOT_LIGHTS > 0
+    for (int i = 0; i < min(spotLightCount, MAX_SPOT_LIGHTS); i++) {
+        SpotLight light = spotLights[i];
+        vec3 toLight = light.position - worldPosition;
+        float lightDistance = length(toLight);
+        vec3 lightDir = toLight / lightDistance;
+        float theta = dot(lightDir, normalize(-light.direction));
+        float cone = clamp((theta - light.outerCutoff) / max(light.innerCutoff - light.outerCutoff, 0.0001), 0.0, 1.0);
+        vec3 radiance = light.color * light.intensity * attenuation(lightDistance, light.range) * cone;
+        color += shade(surface, lightDir, radiance);
+    }
+#endif
+
+    vec3 emission = emissive;
+#ifdef EMISSIVE_TEXTURE
+    emission *= texture(emissiveTexture, texCoord).rgb;
+#endif
+    color += emission;
+
+#ifdef GAMMA_CORRECT
+    color = pow(color, vec3(1.0 / 2.2));
+#endif
+
+    FragColor = vec4(color, albedo.a);
+}
+"#;
+
+/// The lighting model of a built-in lit shader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightingModel {
+    /// Classic diffuse plus specular highlight lighting, using `specularColor` and `shininess`.
+    BlinnPhong,
+    /// Physically based metallic-roughness lighting, using `metallic` and `roughness`.
+    Pbr,
+}
+
+/// Describes a built-in lit shader for meshes with the standard vertex layout (see [`Mesh::new_standard`](crate::Mesh::new_standard)).
+/// Features that are turned off are compiled out of the shader.
+///
+/// The shader uses the `model`, `view`, `projection` and `viewPos` uniforms set by [`GlWindow::render`](crate::GlWindow::render),
+/// the lights set by [`Lights::apply`] and the material values set by [`LitShaderConfig::default_material`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LitShaderConfig {
+    pub lighting_model: LightingModel,
+    pub max_directional_lights: u32,
+    pub max_point_lights: u32,
+    pub max_spot_lights: u32,
+    /// Multiplies `baseColor` with the `baseColorTexture`.
+    pub base_color_texture: bool,
+    /// Perturbs normals with the tangent space `normalTexture`, which needs vertex tangents.
+    pub normal_mapping: bool,
+    /// Multiplies `emissive` with the `emissiveTexture`.
+    pub emissive_texture: bool,
+    /// Multiplies `roughness` and `metallic` with the green and blue channels of the `metallicRoughnessTexture`. PBR only.
+    pub metallic_roughness_texture: bool,
+    /// Converts the output to gamma space. Turn it off when rendering into a float target that is tonemapped later.
+    pub gamma_correct: bool,
+}
+
+impl Default for LitShaderConfig {
+    fn default() -> Self {
+        LitShaderConfig {
+            lighting_model: LightingModel::BlinnPhong,
+            max_directional_lights: 1,
+            max_point_lights: 4,
+            max_spot_lights: 4,
+            base_color_texture: false,
+            normal_mapping: false,
+            emissive_texture: false,
+            metallic_roughness_texture: false,
+            gamma_correct: true,
+        }
+    }
+}
+
+impl LitShaderConfig {
+    /// Returns the GLSL source of the vertex shader.
+    pub fn vertex_source(&self) -> String {
+        self.header() + LIT_VERTEX_SHADER
+    }
+
+    /// Returns the GLSL source of the fragment shader.
+    pub fn fragment_source(&self) -> String {
+        self.header() + LIT_FRAGMENT_SHADER
+    }
+
+    /// Compiles the shader.
+    pub fn build(&self) -> Shader {
+        Shader::new_from_source(&self.vertex_source(), &self.fragment_source())
+    }
+
+    /// Creates a material for the shader with default values for every uniform it uses.
+    /// Textures enabled in the config still have to be set with [`Material::set_texture`].
+    pub fn default_material(&self, shader: Rc<Shader>) -> Material {
+        let mut material = Material::new(shader);
+        material.set_uniform("baseColor", Vec4::ONE);
+        material.set_uniform("emissive", Vec3::ZERO);
+
+        if self.normal_mapping {
+            material.set_uniform("normalScale", 1.0);
+        }
+
+        match self.lighting_model {
+            LightingModel::BlinnPhong => {
+                material.set_uniform("specularColor", Vec3::splat(0.5));
+                material.set_uniform("shininess", 32.0);
+            }
+            LightingModel::Pbr => {
+                material.set_uniform("metallic", 0.0);
+                material.set_uniform("roughness", 0.5);
+            }
+        }
+
+        material
+    }
+
+    fn header(&self) -> String {
+        let mut header = String::from("#version 330 core\n");
+        header += &format!("#define MAX_DIRECTIONAL_LIGHTS {}\n", self.max_directional_lights);
+        header += &format!("#define MAX_POINT_LIGHTS {}\n", self.max_point_lights);
+        header += &format!("#define MAX_SPOT_LIGHTS {}\n", self.max_spot_lights);
+
+        let features = [
+            ("PBR", self.lighting_model == LightingModel::Pbr),
+            ("BASE_COLOR_TEXTURE", self.base_color_texture),
+            ("NORMAL_MAPPING", self.normal_mapping),
+            ("EMISSIVE_TEXTURE", self.emissive_texture),
+            ("METALLIC_ROUGHNESS_TEXTURE", self.metallic_roughness_texture),
+            ("GAMMA_CORRECT", self.gamma_correct),
+        ];
+        for (name, enabled) in features {
+            if enabled {
+                header += &format!("#define {}\n", name);
+            }
+        }
+
+        header
+    }
+}
+
+/// A light infinitely far away shining in one direction, like the sun.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DirectionalLight {
+    pub direction: Vec3,
+    pub color: Vec3,
+    pub intensity: f32,
+}
+
+/// A light shining in all directions from a point, fading out until its range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointLight {
+    pub position: Vec3,
+    pub color: Vec3,
+    pub intensity: f32,
+    pub range: f32,
+}
+
+/// A light shining in a cone from a point. Full intensity inside the inner angle, fading to nothing at the outer angle.
+/// Angles are measured from the center of the cone in radians.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpotLight {
+    pub position: Vec3,
+    pub direction: Vec3,
+    pub color: Vec3,
+    pub intensity: f32,
+    pub range: f32,
+    pub inner_angle: f32,
+    pub outer_angle: f32,
+}
+
+/// The lights of a scene for the built-in lit shaders. Lights beyond the maximum counts of a shader are ignored.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Lights {
+    pub ambient: Vec3,
+    pub directional: Vec<DirectionalLight>,
+    pub point: Vec<PointLight>,
+    pub spot: Vec<SpotLight>,
+}
+
+impl Lights {
+    /// Binds the shader program and uploads the lights. Uniforms are stored per program,
+    /// so this only needs to be called again when the lights change.
+    pub fn apply(&self, shader: &Shader) {
+        shader.bind_program();
+        set_vec3(shader, "ambientColor", self.ambient);
+
+        shader.set_uniform_1i("directionalLightCount", self.directional.len() as i32);
+        for (i, light) in self.directional.iter().enumerate() {
+            let name = format!("directionalLights[{}]", i);
+            set_vec3(shader, &format!("{}.direction", name), light.direction.normalize_or_zero());
+            set_vec3(shader, &format!("{}.color", name), light.color);
+            shader.set_uniform_1f(&format!("{}.intensity", name), light.intensity);
+        }
+
+        shader.set_uniform_1i("pointLightCount", self.point.len() as i32);
+        for (i, light) in self.point.iter().enumerate() {
+            let name = format!("pointLights[{}]", i);
+            set_vec3(shader, &format!("{}.position", name), light.position);
+            set_vec3(shader, &format!("{}.color", name), light.color);
+            shader.set_uniform_1f(&format!("{}.intensity", name), light.intensity);
+            shader.set_uniform_1f(&format!("{}.range", name), light.range);
+        }
+
+        shader.set_uniform_1i("spotLightCount", self.spot.len() as i32);
+        for (i, light) in self.spot.iter().enumerate() {
+            let name = format!("spotLights[{}]", i);
+            set_vec3(shader, &format!("{}.position", name), light.position);
+            set_vec3(shader, &format!("{}.direction", name), light.direction.normalize_or_zero());
+            set_vec3(shader, &format!("{}.color", name), light.color);
+            shader.set_uniform_1f(&format!("{}.intensity", name), light.intensity);
+            shader.set_uniform_1f(&format!("{}.range", name), light.range);
+            shader.set_uniform_1f(&format!("{}.innerCutoff", name), light.inner_angle.cos());
+            shader.set_uniform_1f(&format!("{}.outerCutoff", name), light.outer_angle.cos());
+        }
+    }
+}
+
+fn set_vec3(shader: &Shader, name: &str, value: Vec3) {
+    shader.set_uniform_3f(name, value.x, value.y, value.z);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UniformValue;
+
+    fn defines(config: &LitShaderConfig) -> Vec<String> {
+        config
+            .header()
+            .lines()
+            .filter_map(|line| line.strip_prefix("#define "))
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn header_defines_light_counts_and_default_features() {
+        let config = LitShaderConfig::default();
+        assert!(config.header().starts_with("#version 330 core\n"));
+        assert_eq!(
+            defines(&config),
+            ["MAX_DIRECTIONAL_LIGHTS 1", "MAX_POINT_LIGHTS 4", "MAX_SPOT_LIGHTS 4", "GAMMA_CORRECT"]
+        );
+    }
+
+    #[test]
+    fn header_defines_enabled_features_only() {
+        let config = LitShaderConfig {
+            lighting_model: LightingModel::Pbr,
+            max_directional_lights: 2,
+            max_point_lights: 0,
+            max_spot_lights: 8,
+            base_color_texture: true,
+            normal_mapping: true,
+            emissive_texture: false,
+            metallic_roughness_texture: true,
+            gamma_correct: false,
+        };
+
+        assert_eq!(
+            defines(&config),
+            [
+                "MAX_DIRECTIONAL_LIGHTS 2",
+                "MAX_POINT_LIGHTS 0",
+                "MAX_SPOT_LIGHTS 8",
+                "PBR",
+                "BASE_COLOR_TEXTURE",
+                "NORMAL_MAPPING",
+                "METALLIC_ROUGHNESS_TEXTURE",
+            ]
+        );
+    }
+
+    #[test]
+    fn shader_sources_only_test_defined_features() {
+        let config = LitShaderConfig {
+            lighting_model: LightingModel::Pbr,
+            base_color_texture: true,
+            normal_mapping: true,
+            emissive_texture: true,
+            metallic_roughness_texture: true,
+            ..Default::default()
+        };
+        let defines = defines(&config);
+
+        for source in [config.vertex_source(), config.fragment_source()] {
+            assert_eq!(source.matches("#version").count(), 1);
+            for feature in source.lines().filter_map(|line| line.trim().strip_prefix("#ifdef ")) {
+                assert!(defines.iter().any(|define| define == feature), "{} is never defined", feature);
+            }
+        }
+    }
+
+    #[test]
+    fn default_material_matches_the_lighting_model() {
+        let shader = Rc::new(Shader::without_program());
+
+        let blinn_phong = LitShaderConfig::default().default_material(Rc::clone(&shader));
+        assert!(blinn_phong.uniform("shininess").is_some());
+        assert!(blinn_phong.uniform("roughness").is_none());
+        assert!(blinn_phong.uniform("normalScale").is_none());
+
+        let pbr = LitShaderConfig {
+            lighting_model: LightingModel::Pbr,
+            normal_mapping: true,
+            ..Default::default()
+        }
+        .default_material(shader);
+        assert_eq!(pbr.uniform("roughness"), Some(&UniformValue::Float(0.5)));
+        assert_eq!(pbr.uniform("normalScale"), Some(&UniformValue::Float(1.0)));
+        assert!(pbr.uniform("shininess").is_none());
+    }
+}
@@ -0,0 +1,341 @@
+extern crate gl;
+extern crate glam;
+
+use gl::types::*;
+use glam::{Mat3, Mat4, Vec2, Vec3, Vec4};
+use std::rc::Rc;
+
+use crate::{CubemapTexture, Shader, Texture, Texture3D, TextureArray};
+
+/// A value that can be stored in a [`Material`] and uploaded as a uniform.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UniformValue {
+    Int(i32),
+    Float(f32),
+    Vec2(Vec2),
+    Vec3(Vec3),
+    Vec4(Vec4),
+    Mat3(Mat3),
+    Mat4(Mat4),
+}
+
+impl UniformValue {
+    fn upload(&self, location: GLint) {
+        unsafe {
+            match self {
+                UniformValue::Int(value) => gl::Uniform1i(location, *value),
+                UniformValue::Float(value) => gl::Uniform1f(location, *value),
+                UniformValue::Vec2(value) => gl::Uniform2f(location, value.x, value.y),
+                UniformValue::Vec3(value) => gl::Uniform3f(location, value.x, value.y, value.z),
+                UniformValue::Vec4(value) => gl::Uniform4f(location, value.x, value.y, value.z, value.w),
+                UniformValue::Mat3(value) => {
+                    gl::UniformMatrix3fv(location, 1, gl::FALSE, value.to_cols_array().as_ptr())
+                }
+                UniformValue::Mat4(value) => {
+                    gl::UniformMatrix4fv(location, 1, gl::FALSE, value.to_cols_array().as_ptr())
+                }
+            }
+        }
+    }
+}
+
+impl From<i32> for UniformValue {
+    fn from(value: i32) -> Self {
+        UniformValue::Int(value)
+    }
+}
+
+impl From<bool> for UniformValue {
+    fn from(value: bool) -> Self {
+        UniformValue::Int(value as i32)
+    }
+}
+
+impl From<f32> for UniformValue {
+    fn from(value: f32) -> Self {
+        UniformValue::Float(value)
+    }
+}
+
+impl From<Vec2> for UniformValue {
+    fn from(value: Vec2) -> Self {
+        UniformValue::Vec2(value)
+    }
+}
+
+impl From<Vec3> for UniformValue {
+    fn from(value: Vec3) -> Self {
+        UniformValue::Vec3(value)
+    }
+}
+
+impl From<Vec4> for UniformValue {
+    fn from(value: Vec4) -> Self {
+        UniformValue::Vec4(value)
+    }
+}
+
+impl From<Mat3> for UniformValue {
+    fn from(value: Mat3) -> Self {
+        UniformValue::Mat3(value)
+    }
+}
+
+impl From<Mat4> for UniformValue {
+    fn from(value: Mat4) -> Self {
+        UniformValue::Mat4(value)
+    }
+}
+
+/// A texture of any kind that can be assigned to a [`Material`] slot.
+#[derive(Clone)]
+pub enum MaterialTexture {
+    Texture(Rc<Texture>),
+    Cubemap(Rc<CubemapTexture>),
+    Array(Rc<TextureArray>),
+    Texture3D(Rc<Texture3D>),
+}
+
+impl MaterialTexture {
+    fn bind(&self, texture_unit: u32) {
+        match self {
+            MaterialTexture::Texture(texture) => texture.bind(texture_unit),
+            MaterialTexture::Cubemap(texture) => texture.bind(texture_unit),
+            MaterialTexture::Array(texture) => texture.bind(texture_unit),
+            MaterialTexture::Texture3D(texture) => texture.bind(texture_unit),
+        }
+    }
+
+    fn unbind(&self, texture_unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + texture_unit);
+        }
+        match self {
+            MaterialTexture::Texture(texture) => texture.unbind(),
+            MaterialTexture::Cubemap(texture) => texture.unbind(),
+            MaterialTexture::Array(texture) => texture.unbind(),
+            MaterialTexture::Texture3D(texture) => texture.unbind(),
+        }
+    }
+}
+
+impl From<Rc<Texture>> for MaterialTexture {
+    fn from(texture: Rc<Texture>) -> Self {
+        MaterialTexture::Texture(texture)
+    }
+}
+
+impl From<Rc<CubemapTexture>> for MaterialTexture {
+    fn from(texture: Rc<CubemapTexture>) -> Self {
+        MaterialTexture::Cubemap(texture)
+    }
+}
+
+impl From<Rc<TextureArray>> for MaterialTexture {
+    fn from(texture: Rc<TextureArray>) -> Self {
+        MaterialTexture::Array(texture)
+    }
+}
+
+impl From<Rc<Texture3D>> for MaterialTexture {
+    fn from(texture: Rc<Texture3D>) -> Self {
+        MaterialTexture::Texture3D(texture)
+    }
+}
+
+/// A shader together with the textures and uniform values it is drawn with, applied in one call before rendering.
+/// Textures are assigned to texture units in the order their slots were added, and the matching sampler uniforms are set automatically.
+///
+/// Materials created from the same `Rc<Shader>` share its program and cached uniform locations, so cloning a material
+/// and changing a few values is a cheap way to make variants.
+#[derive(Clone)]
+pub struct Material {
+    shader: Rc<Shader>,
+    textures: Vec<(String, MaterialTexture)>,
+    uniforms: Vec<(String, UniformValue)>,
+}
+
+impl Material {
+    /// Creates a material without textures or uniform values.
+    pub fn new(shader: Rc<Shader>) -> Self {
+        Material {
+            shader,
+            textures: Vec::new(),
+            uniforms: Vec::new(),
+        }
+    }
+
+    /// Returns the shader the material is drawn with.
+    pub fn shader(&self) -> &Rc<Shader> {
+        &self.shader
+    }
+
+    /// Returns true if both materials are drawn with the same shader program.
+    pub fn shares_shader(&self, other: &Material) -> bool {
+        Rc::ptr_eq(&self.shader, &other.shader)
+    }
+
+    /// Sets the texture of a sampler uniform. Replacing the texture of an existing slot keeps its texture unit.
+    pub fn set_texture(&mut self, name: &str, texture: impl Into<MaterialTexture>) {
+        let texture = texture.into();
+        match self.textures.iter_mut().find(|(slot, _)| slot == name) {
+            Some((_, slot_texture)) => *slot_texture = texture,
+            None => self.textures.push((name.to_string(), texture)),
+        }
+    }
+
+    /// Returns the texture of a sampler uniform.
+    pub fn texture(&self, name: &str) -> Option<&MaterialTexture> {
+        self.textures.iter().find(|(slot, _)| slot == name).map(|(_, texture)| texture)
+    }
+
+    /// Removes a texture slot. Texture units of the following slots move down by one.
+    pub fn remove_texture(&mut self, name: &str) {
+        self.textures.retain(|(slot, _)| slot != name);
+    }
+
+    /// Returns the texture unit a texture slot is bound to when the material is applied.
+    pub fn texture_unit(&self, name: &str) -> Option<u32> {
+        self.textures.iter().position(|(slot, _)| slot == name).map(|unit| unit as u32)
+    }
+
+    /// Returns the number of texture units the material uses, starting at unit 0.
+    pub fn texture_count(&self) -> u32 {
+        self.textures.len() as u32
+    }
+
+    /// Sets the value of a uniform.
+    pub fn set_uniform(&mut self, name: &str, value: impl Into<UniformValue>) {
+        let value = value.into();
+        match self.uniforms.iter_mut().find(|(uniform, _)| uniform == name) {
+            Some((_, uniform_value)) => *uniform_value = value,
+            None => self.uniforms.push((name.to_string(), value)),
+        }
+    }
+
+    /// Returns the value of a uniform.
+    pub fn uniform(&self, name: &str) -> Option<&UniformValue> {
+        self.uniforms.iter().find(|(uniform, _)| uniform == name).map(|(_, value)| value)
+    }
+
+    /// Removes a uniform value. The shader keeps the last value that was uploaded.
+    pub fn remove_uniform(&mut self, name: &str) {
+        self.uniforms.retain(|(uniform, _)| uniform != name);
+    }
+
+    /// Binds the shader program, binds every texture to its unit and uploads all uniform values.
+    /// Uniforms that don't exist in the shader are skipped.
+    pub fn apply(&self) {
+        self.shader.bind_program();
+
+        for (unit, (name, texture)) in self.textures.iter().enumerate() {
+            texture.bind(unit as u32);
+            self.shader.set_uniform_texture(name, unit as u32);
+        }
+
+        for (name, value) in &self.uniforms {
+            if let Some(location) = self.shader.uniform_location(name) {
+                value.upload(location);
+            }
+        }
+    }
+
+    /// Unbinds the textures and the shader program.
+    pub fn unbind(&self) {
+        for (unit, (_, texture)) in self.textures.iter().enumerate() {
+            texture.unbind(unit as u32);
+        }
+
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+        self.shader.unbind_program();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TextureFormat;
+
+    fn material() -> Material {
+        Material::new(Rc::new(Shader::without_program()))
+    }
+
+    /// Creates a texture that is never dropped, since deleting it would need an OpenGL context.
+    fn texture() -> Rc<Texture> {
+        let texture = Rc::new(Texture {
+            id: 0,
+            width: 1,
+            height: 1,
+            format: TextureFormat::Rgba8,
+        });
+        std::mem::forget(Rc::clone(&texture));
+        texture
+    }
+
+    #[test]
+    fn texture_units_follow_slot_order() {
+        let mut material = material();
+        material.set_texture("albedo", texture());
+        material.set_texture("normal", texture());
+        material.set_texture("roughness", texture());
+
+        assert_eq!(material.texture_count(), 3);
+        assert_eq!(material.texture_unit("albedo"), Some(0));
+        assert_eq!(material.texture_unit("normal"), Some(1));
+        assert_eq!(material.texture_unit("roughness"), Some(2));
+        assert_eq!(material.texture_unit("missing"), None);
+    }
+
+    #[test]
+    fn replacing_a_texture_keeps_its_unit() {
+        let mut material = material();
+        let replacement = texture();
+        material.set_texture("albedo", texture());
+        material.set_texture("normal", texture());
+        material.set_texture("albedo", Rc::clone(&replacement));
+
+        assert_eq!(material.texture_count(), 2);
+        assert_eq!(material.texture_unit("albedo"), Some(0));
+        let Some(MaterialTexture::Texture(albedo)) = material.texture("albedo") else {
+            panic!("the albedo slot should hold a 2D texture");
+        };
+        assert!(Rc::ptr_eq(albedo, &replacement));
+    }
+
+    #[test]
+    fn removing_a_texture_moves_later_units_down() {
+        let mut material = material();
+        material.set_texture("albedo", texture());
+        material.set_texture("normal", texture());
+        material.set_texture("roughness", texture());
+
+        material.remove_texture("normal");
+        assert_eq!(material.texture_count(), 2);
+        assert_eq!(material.texture_unit("albedo"), Some(0));
+        assert_eq!(material.texture_unit("normal"), None);
+        assert_eq!(material.texture_unit("roughness"), Some(1));
+        assert!(material.texture("normal").is_none());
+
+        // Removing a slot that doesn't exist changes nothing
+        material.remove_texture("normal");
+        assert_eq!(material.texture_count(), 2);
+    }
+
+    #[test]
+    fn uniforms_are_replaced_and_removed_by_name() {
+        let mut material = material();
+        material.set_uniform("roughness", 0.5);
+        material.set_uniform("tint", Vec3::ONE);
+        material.set_uniform("roughness", 0.25);
+
+        assert_eq!(material.uniform("roughness"), Some(&UniformValue::Float(0.25)));
+        assert_eq!(material.uniform("tint"), Some(&UniformValue::Vec3(Vec3::ONE)));
+
+        material.remove_uniform("tint");
+        assert_eq!(material.uniform("tint"), None);
+        assert!(material.shares_shader(&material.clone()));
+        assert!(!material.shares_shader(&self::material()));
+    }
+}
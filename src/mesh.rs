@@ -3,8 +3,21 @@ extern crate glam;
 extern crate glfw;
 
 use gl::types::*;
+use glam::{Vec2, Vec3};
 use std::mem;
 
+/// The standard vertex layout used by the built-in lit shaders: position (location 0), normal (1),
+/// texture coordinate (2) and tangent (3), with the tangent's w storing the handedness of the bitangent.
+pub const STANDARD_VERTEX_ATTRIBUTES: [(u32, i32, GLenum, bool); 4] = [
+    (0, 3, gl::FLOAT, false),
+    (1, 3, gl::FLOAT, false),
+    (2, 2, gl::FLOAT, false),
+    (3, 4, gl::FLOAT, false),
+];
+
+/// The number of floats per vertex in the standard vertex layout.
+pub const STANDARD_VERTEX_SIZE: usize = 12;
+
 /// A struct to handle a mesh for rendering.
 pub struct Mesh {
     vertex_array: u32,
@@ -130,6 +143,15 @@ impl Mesh {
         }
     }
 
+    /// Creates a mesh with the standard vertex layout, see [`STANDARD_VERTEX_ATTRIBUTES`].
+    pub fn new_standard(vertices: &[f32], indices: &[u32]) -> Self {
+        let mut mesh = Mesh::new();
+        mesh.update_vertices(vertices);
+        mesh.update_indices(indices);
+        mesh.add_vertex_attributes(&STANDARD_VERTEX_ATTRIBUTES);
+        mesh
+    }
+
     /// Clears all buffers.
     pub fn remesh(&mut self) {
         unsafe {
@@ -141,3 +163,115 @@ impl Mesh {
         *self = Mesh::new();
     }
 }
+
+/// Calculates the tangents of triangles in the standard vertex layout from their positions and texture coordinates,
+/// overwriting the tangent of every vertex. Tangents are needed for normal mapping.
+/// Fails without changing anything if the vertices or indices don't form whole triangles of existing vertices.
+pub fn generate_tangents(vertices: &mut [f32], indices: &[u32]) -> Result<(), String> {
+    if !vertices.len().is_multiple_of(STANDARD_VERTEX_SIZE) {
+        return Err(format!(
+            "[FerrousGl Error] Vertex data has {} floats, which is not a multiple of the {} floats per standard vertex.",
+            vertices.len(),
+            STANDARD_VERTEX_SIZE
+        ));
+    }
+    if !indices.len().is_multiple_of(3) {
+        return Err(format!("[FerrousGl Error] {} indices do not form whole triangles.", indices.len()));
+    }
+
+    let vertex_count = vertices.len() / STANDARD_VERTEX_SIZE;
+    if let Some(index) = indices.iter().find(|&&index| index as usize >= vertex_count) {
+        return Err(format!(
+            "[FerrousGl Error] Index {} is out of range for {} vertices.",
+            index, vertex_count
+        ));
+    }
+
+    let mut tangents = vec![Vec3::ZERO; vertex_count];
+    let mut bitangents = vec![Vec3::ZERO; vertex_count];
+
+    let position = |vertices: &[f32], i: usize| Vec3::from_slice(&vertices[i * STANDARD_VERTEX_SIZE..]);
+    let normal = |vertices: &[f32], i: usize| Vec3::from_slice(&vertices[i * STANDARD_VERTEX_SIZE + 3..]);
+    let uv = |vertices: &[f32], i: usize| Vec2::from_slice(&vertices[i * STANDARD_VERTEX_SIZE + 6..]);
+
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0] as usize, triangle[1] as usize, triangle[2] as usize];
+        let edge_1 = position(vertices, b) - position(vertices, a);
+        let edge_2 = position(vertices, c) - position(vertices, a);
+        let delta_uv_1 = uv(vertices, b) - uv(vertices, a);
+        let delta_uv_2 = uv(vertices, c) - uv(vertices, a);
+
+        let determinant = delta_uv_1.x * delta_uv_2.y - delta_uv_2.x * delta_uv_1.y;
+        if determinant.abs() < f32::EPSILON {
+            continue;
+        }
+
+        let tangent = (edge_1 * delta_uv_2.y - edge_2 * delta_uv_1.y) / determinant;
+        let bitangent = (edge_2 * delta_uv_1.x - edge_1 * delta_uv_2.x) / determinant;
+        for index in [a, b, c] {
+            tangents[index] += tangent;
+            bitangents[index] += bitangent;
+        }
+    }
+
+    for i in 0..vertex_count {
+        let normal = normal(vertices, i);
+        // Make the tangent perpendicular to the normal, falling back to any perpendicular direction
+        let mut tangent = (tangents[i] - normal * normal.dot(tangents[i])).normalize_or_zero();
+        if tangent == Vec3::ZERO {
+            tangent = normal.any_orthonormal_vector();
+        }
+        let handedness = if normal.cross(tangent).dot(bitangents[i]) < 0.0 { -1.0 } else { 1.0 };
+
+        let offset = i * STANDARD_VERTEX_SIZE + 8;
+        vertices[offset..offset + 4].copy_from_slice(&[tangent.x, tangent.y, tangent.z, handedness]);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A unit quad in the XY plane facing +Z, with `v` running along +Y or -Y.
+    fn xy_quad(flip_v: bool) -> Vec<f32> {
+        let mut vertices = Vec::new();
+        for (x, y) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+            let v = if flip_v { 1.0 - y } else { y };
+            vertices.extend_from_slice(&[x, y, 0.0, 0.0, 0.0, 1.0, x, v, 0.0, 0.0, 0.0, 0.0]);
+        }
+        vertices
+    }
+
+    fn tangent(vertices: &[f32], i: usize) -> [f32; 4] {
+        let offset = i * STANDARD_VERTEX_SIZE + 8;
+        vertices[offset..offset + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn generate_tangents_for_xy_quad() {
+        let indices = [0, 1, 2, 0, 2, 3];
+
+        let mut vertices = xy_quad(false);
+        generate_tangents(&mut vertices, &indices).unwrap();
+        for i in 0..4 {
+            assert_eq!(tangent(&vertices, i), [1.0, 0.0, 0.0, 1.0]);
+        }
+
+        let mut vertices = xy_quad(true);
+        generate_tangents(&mut vertices, &indices).unwrap();
+        for i in 0..4 {
+            assert_eq!(tangent(&vertices, i), [1.0, 0.0, 0.0, -1.0]);
+        }
+    }
+
+    #[test]
+    fn generate_tangents_rejects_invalid_input() {
+        let mut vertices = xy_quad(false);
+        assert!(generate_tangents(&mut vertices, &[0, 1, 4]).is_err());
+        assert!(generate_tangents(&mut vertices, &[0, 1]).is_err());
+        assert!(generate_tangents(&mut vertices[..47], &[0, 1, 2]).is_err());
+        assert_eq!(vertices, xy_quad(false));
+    }
+}
@@ -0,0 +1,184 @@
+extern crate image;
+
+use image::{DynamicImage, Rgba32FImage};
+
+/// Generates the mip chain of an image on the CPU, returning every level below the image itself down to 1x1.
+/// Each level is a box filter of the previous one, 2x2 for even sizes and 3 taps wide along odd sides. With `gamma_correct`, the color channels are treated as
+/// sRGB and averaged in linear space, which keeps bright and dark details from turning muddy in smaller levels.
+/// Alpha is always averaged as is.
+pub fn generate_mip_chain(img: &DynamicImage, gamma_correct: bool) -> Vec<DynamicImage> {
+    let mut current = img.to_rgba32f();
+    if gamma_correct {
+        map_color(&mut current, srgb_to_linear);
+    }
+
+    let mut levels = Vec::new();
+    while current.width() > 1 || current.height() > 1 {
+        current = downsample(&current);
+
+        let mut level = current.clone();
+        if gamma_correct {
+            map_color(&mut level, linear_to_srgb);
+        }
+        levels.push(DynamicImage::ImageRgba32F(level));
+    }
+
+    levels
+}
+
+/// Returns the number of mip levels of a full chain for the given size, including the full size level.
+pub fn mip_level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+/// Halves the image with a box filter. Along even sides each texel averages a 2 texel block, along odd sides it
+/// weighs 3 texels so every source texel contributes equally and the last row or column is not dropped.
+fn downsample(img: &Rgba32FImage) -> Rgba32FImage {
+    let (width, height) = img.dimensions();
+    let (new_width, new_height) = ((width / 2).max(1), (height / 2).max(1));
+
+    Rgba32FImage::from_fn(new_width, new_height, |x, y| {
+        let mut sum = [0.0f32; 4];
+        for (sy, weight_y) in filter_taps(height, new_height, y) {
+            for (sx, weight_x) in filter_taps(width, new_width, x) {
+                let weight = weight_x * weight_y;
+                if weight == 0.0 {
+                    continue;
+                }
+                for (channel, value) in sum.iter_mut().zip(img.get_pixel(sx, sy).0) {
+                    *channel += value * weight;
+                }
+            }
+        }
+
+        image::Rgba(sum)
+    })
+}
+
+/// Returns the source texels and weights that make up texel `i` of one downsampled axis.
+/// An odd size `2n + 1` uses the weights `(n - i, n, i + 1) / (2n + 1)`, which sum to 1 for every texel.
+fn filter_taps(size: u32, new_size: u32, i: u32) -> [(u32, f32); 3] {
+    if size == 1 {
+        [(0, 1.0), (0, 0.0), (0, 0.0)]
+    } else if size.is_multiple_of(2) {
+        [(i * 2, 0.5), (i * 2 + 1, 0.5), (i * 2 + 1, 0.0)]
+    } else {
+        let size_f = size as f32;
+        [
+            (i * 2, (new_size - i) as f32 / size_f),
+            (i * 2 + 1, new_size as f32 / size_f),
+            (i * 2 + 2, (i + 1) as f32 / size_f),
+        ]
+    }
+}
+
+fn map_color(img: &mut Rgba32FImage, f: fn(f32) -> f32) {
+    for pixel in img.pixels_mut() {
+        for channel in &mut pixel.0[..3] {
+            *channel = f(*channel);
+        }
+    }
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+
+    #[test]
+    fn mip_level_count_covers_the_largest_side() {
+        assert_eq!(mip_level_count(0, 0), 1);
+        assert_eq!(mip_level_count(1, 1), 1);
+        assert_eq!(mip_level_count(2, 1), 2);
+        assert_eq!(mip_level_count(256, 256), 9);
+        assert_eq!(mip_level_count(300, 17), 9);
+        assert_eq!(mip_level_count(1, 1024), 11);
+        assert_eq!(mip_level_count(u32::MAX, 1), 32);
+    }
+
+    #[test]
+    fn mip_chain_halves_down_to_one_pixel() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::new(10, 3));
+        let sizes: Vec<(u32, u32)> = generate_mip_chain(&img, false)
+            .iter()
+            .map(|level| (level.width(), level.height()))
+            .collect();
+
+        assert_eq!(sizes, [(5, 1), (2, 1), (1, 1)]);
+        assert_eq!(sizes.len() as u32, mip_level_count(10, 3) - 1);
+        assert!(generate_mip_chain(&DynamicImage::ImageRgba8(RgbaImage::new(1, 1)), false).is_empty());
+    }
+
+    #[test]
+    fn mip_chain_averages_pixels() {
+        let img = RgbaImage::from_fn(2, 2, |x, _| if x == 0 { Rgba([255, 0, 0, 255]) } else { Rgba([0, 0, 255, 0]) });
+        let levels = generate_mip_chain(&DynamicImage::ImageRgba8(img), false);
+
+        let pixel = levels[0].to_rgba32f().get_pixel(0, 0).0;
+        assert_eq!(pixel, [0.5, 0.0, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn odd_sizes_weigh_every_texel() {
+        // 5 -> 2 must read the last column, and every source texel must carry the same total weight
+        let img = RgbaImage::from_fn(5, 1, |x, _| if x == 4 { Rgba([255, 255, 255, 255]) } else { Rgba([0, 0, 0, 255]) });
+        let level = generate_mip_chain(&DynamicImage::ImageRgba8(img), false)[0].to_rgba32f();
+
+        assert_eq!(level.dimensions(), (2, 1));
+        assert_eq!(level.get_pixel(0, 0).0[0], 0.0);
+        assert!((level.get_pixel(1, 0).0[0] - 0.4).abs() < 1e-6);
+
+        for (size, new_size) in [(3, 1), (5, 2), (7, 3)] {
+            let mut total = vec![0.0f32; size as usize];
+            for i in 0..new_size {
+                let taps = filter_taps(size, new_size, i);
+                assert!((taps.iter().map(|(_, weight)| weight).sum::<f32>() - 1.0).abs() < 1e-6);
+                for (texel, weight) in taps {
+                    total[texel as usize] += weight;
+                }
+            }
+            for weight in total {
+                assert!((weight - new_size as f32 / size as f32).abs() < 1e-6, "{} -> {}", size, new_size);
+            }
+        }
+    }
+
+    #[test]
+    fn gamma_correct_mip_chain_averages_in_linear_space() {
+        let img = RgbaImage::from_fn(2, 1, |x, _| if x == 0 { Rgba([255, 255, 255, 255]) } else { Rgba([0, 0, 0, 255]) });
+        let img = DynamicImage::ImageRgba8(img);
+
+        let linear = generate_mip_chain(&img, false)[0].to_rgba32f().get_pixel(0, 0).0;
+        let gamma_correct = generate_mip_chain(&img, true)[0].to_rgba32f().get_pixel(0, 0).0;
+
+        assert_eq!(linear[0], 0.5);
+        // Half the light of white is brighter than 0.5 in sRGB
+        assert!((gamma_correct[0] - linear_to_srgb(0.5)).abs() < 1e-5);
+        assert!(gamma_correct[0] > 0.7);
+        assert_eq!(gamma_correct[3], 1.0);
+    }
+
+    #[test]
+    fn srgb_conversion_round_trips() {
+        for i in 0..=20 {
+            let value = i as f32 / 20.0;
+            assert!((linear_to_srgb(srgb_to_linear(value)) - value).abs() < 1e-5);
+        }
+    }
+}
@@ -0,0 +1,635 @@
+extern crate gl;
+
+use gl::types::*;
+use std::ptr;
+use std::rc::Rc;
+
+use crate::texture::TextureFormat;
+use crate::{Mesh, RenderTexture, RenderTextureConfig, Shader, Texture, Texture3D};
+
+const POST_VERTEX_SHADER: &str = r#"
+#version 330 core
+layout (location = 0) in vec2 aPos;
+
+out vec2 uv;
+
+void main() {
+    uv = aPos * 0.5 + 0.5;
+    gl_Position = vec4(aPos, 0.0, 1.0);
+}
+"#;
+
+const COPY_FRAGMENT_SHADER: &str = r#"
+#version 330 core
+in vec2 uv;
+
+uniform sampler2D source;
+
+out vec4 FragColor;
+
+void main() {
+    FragColor = texture(source, uv);
+}
+"#;
+
+const FXAA_FRAGMENT_SHADER: &str = r#"
+#version 330 core
+in vec2 uv;
+
+uniform sampler2D source;
+uniform vec2 texelSize;
+
+out vec4 FragColor;
+
+const float SPAN_MAX = 8.0;
+const float REDUCE_MUL = 1.0 / 8.0;
+const float REDUCE_MIN = 1.0 / 128.0;
+
+float luma(vec3 color) {
+    return dot(color, vec3(0.299, 0.587, 0.114));
+}
+
+void main() {
+    vec4 center = texture(source, uv);
+    float lumaM = luma(center.rgb);
+    float lumaNW = luma(texture(source, uv + vec2(-1.0, -1.0) * texelSize).rgb);
+    float lumaNE = luma(texture(source, uv + vec2(1.0, -1.0) * texelSize).rgb);
+    float lumaSW = luma(texture(source, uv + vec2(-1.0, 1.0) * texelSize).rgb);
+    float lumaSE = luma(texture(source, uv + vec2(1.0, 1.0) * texelSize).rgb);
+
+    float lumaMin = min(lumaM, min(min(lumaNW, lumaNE), min(lumaSW, lumaSE)));
+    float lumaMax = max(lumaM, max(max(lumaNW, lumaNE), max(lumaSW, lumaSE)));
+
+    // Blur along the edge, which runs perpendicular to the luma gradient
+    vec2 direction = vec2(-((lumaNW + lumaNE) - (lumaSW + lumaSE)), (lumaNW + lumaSW) - (lumaNE + lumaSE));
+    float directionReduce = max((lumaNW + lumaNE + lumaSW + lumaSE) * 0.25 * REDUCE_MUL, REDUCE_MIN);
+    float inverseDirectionMin = 1.0 / (min(abs(direction.x), abs(direction.y)) + directionReduce);
+    direction = clamp(direction * inverseDirectionMin, vec2(-SPAN_MAX), vec2(SPAN_MAX)) * texelSize;
+
+    vec3 colorA = 0.5 * (
+        texture(source, uv + direction * (1.0 / 3.0 - 0.5)).rgb +
+        texture(source, uv + direction * (2.0 / 3.0 - 0.5)).rgb);
+    vec3 colorB = colorA * 0.5 + 0.25 * (
+        texture(source, uv + direction * -0.5).rgb +
+        texture(source, uv + direction * 0.5).rgb);
+
+    float lumaB = luma(colorB);
+    FragColor = vec4((lumaB < lumaMin || lumaB > lumaMax) ? colorA : colorB, center.a);
+}
+"#;
+
+const BLOOM_PREFILTER_FRAGMENT_SHADER: &str = r#"
+#version 330 core
+in vec2 uv;
+
+uniform sampler2D source;
+uniform float threshold;
+
+out vec4 FragColor;
+
+void main() {
+    vec3 color = texture(source, uv).rgb;
+    float brightness = max(color.r, max(color.g, color.b));
+
+    // Soft knee, so pixels just below the threshold fade in instead of popping
+    float knee = threshold * 0.5;
+    float soft = clamp(brightness - threshold + knee, 0.0, 2.0 * knee);
+    soft = soft * soft / (4.0 * knee + 0.0001);
+    float contribution = max(soft, brightness - threshold) / max(brightness, 0.0001);
+
+    FragColor = vec4(color * contribution, 1.0);
+}
+"#;
+
+const BLOOM_DOWNSAMPLE_FRAGMENT_SHADER: &str = r#"
+#version 330 core
+in vec2 uv;
+
+uniform sampler2D source;
+uniform vec2 texelSize;
+
+out vec4 FragColor;
+
+void main() {
+    vec2 halfTexel = texelSize * 0.5;
+    vec3 sum = texture(source, uv).rgb * 4.0;
+    sum += texture(source, uv - halfTexel).rgb;
+    sum += texture(source, uv + halfTexel).rgb;
+    sum += texture(source, uv + vec2(halfTexel.x, -halfTexel.y)).rgb;
+    sum += texture(source, uv - vec2(halfTexel.x, -halfTexel.y)).rgb;
+    FragColor = vec4(sum / 8.0, 1.0);
+}
+"#;
+
+const BLOOM_UPSAMPLE_FRAGMENT_SHADER: &str = r#"
+#version 330 core
+in vec2 uv;
+
+uniform sampler2D source;
+uniform vec2 texelSize;
+
+out vec4 FragColor;
+
+void main() {
+    vec2 halfTexel = texelSize * 0.5;
+    vec3 sum = texture(source, uv + vec2(-halfTexel.x * 2.0, 0.0)).rgb;
+    sum += texture(source, uv + vec2(-halfTexel.x, halfTexel.y)).rgb * 2.0;
+    sum += texture(source, uv + vec2(0.0, halfTexel.y * 2.0)).rgb;
+    sum += texture(source, uv + vec2(halfTexel.x, halfTexel.y)).rgb * 2.0;
+    sum += texture(source, uv + vec2(halfTexel.x * 2.0, 0.0)).rgb;
+    sum += texture(source, uv + vec2(halfTexel.x, -halfTexel.y)).rgb * 2.0;
+    sum += texture(source, uv + vec2(0.0, -halfTexel.y * 2.0)).rgb;
+    sum += texture(source, uv + vec2(-halfTexel.x, -halfTexel.y)).rgb * 2.0;
+    FragColor = vec4(sum / 12.0, 1.0);
+}
+"#;
+
+const BLOOM_COMPOSITE_FRAGMENT_SHADER: &str = r#"
+#version 330 core
+in vec2 uv;
+
+uniform sampler2D source;
+uniform sampler2D bloom;
+uniform float intensity;
+
+out vec4 FragColor;
+
+void main() {
+    vec4 color = texture(source, uv);
+    FragColor = vec4(color.rgb + texture(bloom, uv).rgb * intensity, color.a);
+}
+"#;
+
+const TONEMAP_FRAGMENT_SHADER: &str = r#"
+#version 330 core
+in vec2 uv;
+
+uniform sampler2D source;
+uniform int tonemapper;
+uniform float exposure;
+
+out vec4 FragColor;
+
+// Krzysztof Narkowicz's fit of the ACES filmic curve
+vec3 aces(vec3 x) {
+    return clamp((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14), 0.0, 1.0);
+}
+
+void main() {
+    vec4 color = texture(source, uv);
+    vec3 exposed = color.rgb * exposure;
+    vec3 mapped = tonemapper == 0 ? exposed / (exposed + 1.0) : aces(exposed);
+    FragColor = vec4(mapped, color.a);
+}
+"#;
+
+const GAMMA_FRAGMENT_SHADER: &str = r#"
+#version 330 core
+in vec2 uv;
+
+uniform sampler2D source;
+uniform float gamma;
+
+out vec4 FragColor;
+
+void main() {
+    vec4 color = texture(source, uv);
+    FragColor = vec4(pow(max(color.rgb, 0.0), vec3(1.0 / gamma)), color.a);
+}
+"#;
+
+const VIGNETTE_FRAGMENT_SHADER: &str = r#"
+#version 330 core
+in vec2 uv;
+
+uniform sampler2D source;
+uniform float intensity;
+uniform float radius;
+
+out vec4 FragColor;
+
+void main() {
+    vec4 color = texture(source, uv);
+    // 0 in the center, 1 in the corners
+    float edge = length(uv - 0.5) * 1.41421356;
+    float vignette = 1.0 - intensity * smoothstep(radius, 1.0, edge);
+    FragColor = vec4(color.rgb * vignette, color.a);
+}
+"#;
+
+const CHROMATIC_ABERRATION_FRAGMENT_SHADER: &str = r#"
+#version 330 core
+in vec2 uv;
+
+uniform sampler2D source;
+uniform vec2 texelSize;
+uniform float strength;
+
+out vec4 FragColor;
+
+void main() {
+    // Red and blue are shifted apart, more towards the edges
+    vec2 offset = (uv - 0.5) * 2.0 * strength * texelSize;
+    vec4 color = texture(source, uv);
+    color.r = texture(source, uv + offset).r;
+    color.b = texture(source, uv - offset).b;
+    FragColor = color;
+}
+"#;
+
+const COLOR_GRADING_FRAGMENT_SHADER: &str = r#"
+#version 330 core
+in vec2 uv;
+
+uniform sampler2D source;
+uniform sampler3D lut;
+uniform float lutSize;
+uniform float intensity;
+
+out vec4 FragColor;
+
+void main() {
+    vec4 color = texture(source, uv);
+    // Sample the centers of the outermost texels, so 0 and 1 map exactly onto the ends of the table
+    vec3 scale = vec3((lutSize - 1.0) / lutSize);
+    vec3 offset = vec3(0.5 / lutSize);
+    vec3 graded = texture(lut, clamp(color.rgb, 0.0, 1.0) * scale + offset).rgb;
+    FragColor = vec4(mix(color.rgb, graded, intensity), color.a);
+}
+"#;
+
+/// The curve used to map HDR colors into the displayable 0 to 1 range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tonemapper {
+    Reinhard,
+    Aces,
+}
+
+/// A single step of a [`PostProcessStack`].
+#[derive(Clone)]
+pub enum PostEffect {
+    /// Fast approximate anti-aliasing. Works best after tonemapping, on colors in the 0 to 1 range.
+    Fxaa,
+    /// Makes bright parts of the image glow. Pixels brighter than `threshold` are blurred with a dual filter
+    /// over `iterations` half-sized steps and added back with `intensity`.
+    Bloom {
+        threshold: f32,
+        intensity: f32,
+        iterations: u32,
+    },
+    /// Maps HDR colors into the 0 to 1 range after multiplying them with `exposure`.
+    Tonemap {
+        tonemapper: Tonemapper,
+        exposure: f32,
+    },
+    /// Converts linear colors for display, usually with a gamma of 2.2.
+    Gamma(f32),
+    /// Darkens the corners of the image, starting at `radius` (0 is the center, 1 the corners).
+    Vignette { intensity: f32, radius: f32 },
+    /// Splits the red and blue channels apart towards the edges, by up to `strength` pixels.
+    ChromaticAberration { strength: f32 },
+    /// Remaps colors through a 3D lookup table, blended with the original colors by `intensity`.
+    ColorGrading { lut: Rc<Texture3D>, intensity: f32 },
+    /// A custom effect, see [`PostEffect::custom`].
+    Custom(Rc<Shader>),
+}
+
+impl PostEffect {
+    /// Creates a custom effect from just a fragment shader. The shader receives the texture coordinates as
+    /// `in vec2 uv`, the previous result as `uniform sampler2D source` (unit 0) and the size of one of its
+    /// pixels as `uniform vec2 texelSize`. Its own uniforms can be set on the shader at any time.
+    pub fn custom(fragment_source: &str) -> Self {
+        PostEffect::Custom(Rc::new(Shader::new_from_source(POST_VERTEX_SHADER, fragment_source)))
+    }
+}
+
+struct PostShaders {
+    copy: Shader,
+    fxaa: Shader,
+    bloom_prefilter: Shader,
+    bloom_downsample: Shader,
+    bloom_upsample: Shader,
+    bloom_composite: Shader,
+    tonemap: Shader,
+    gamma: Shader,
+    vignette: Shader,
+    chromatic_aberration: Shader,
+    color_grading: Shader,
+}
+
+/// Runs an ordered list of fullscreen effects over a rendered image, ping-ponging between two
+/// HDR render textures and drawing the last effect into the framebuffer that was bound before.
+pub struct PostProcessStack {
+    effects: Vec<PostEffect>,
+    scene: RenderTexture,
+    ping_pong: [RenderTexture; 2],
+    bloom_chain: Vec<RenderTexture>,
+    triangle: Mesh,
+    shaders: PostShaders,
+    width: u32,
+    height: u32,
+}
+
+impl PostProcessStack {
+    /// Creates a new post processing stack for images of the given size, without any effects.
+    pub fn new(width: u32, height: u32) -> Result<Self, String> {
+        let shader = |fragment_source| Shader::new_from_source(POST_VERTEX_SHADER, fragment_source);
+
+        Ok(PostProcessStack {
+            effects: Vec::new(),
+            scene: RenderTexture::new_with_config(RenderTextureConfig {
+                color_formats: vec![TextureFormat::Rgba16F],
+                depth_format: Some(TextureFormat::Depth24),
+                ..RenderTextureConfig::new(width, height)
+            })?,
+            ping_pong: [
+                RenderTexture::new_with_format(width, height, TextureFormat::Rgba16F, false)?,
+                RenderTexture::new_with_format(width, height, TextureFormat::Rgba16F, false)?,
+            ],
+            bloom_chain: Vec::new(),
+            triangle: fullscreen_triangle(),
+            shaders: PostShaders {
+                copy: shader(COPY_FRAGMENT_SHADER),
+                fxaa: shader(FXAA_FRAGMENT_SHADER),
+                bloom_prefilter: shader(BLOOM_PREFILTER_FRAGMENT_SHADER),
+                bloom_downsample: shader(BLOOM_DOWNSAMPLE_FRAGMENT_SHADER),
+                bloom_upsample: shader(BLOOM_UPSAMPLE_FRAGMENT_SHADER),
+                bloom_composite: shader(BLOOM_COMPOSITE_FRAGMENT_SHADER),
+                tonemap: shader(TONEMAP_FRAGMENT_SHADER),
+                gamma: shader(GAMMA_FRAGMENT_SHADER),
+                vignette: shader(VIGNETTE_FRAGMENT_SHADER),
+                chromatic_aberration: shader(CHROMATIC_ABERRATION_FRAGMENT_SHADER),
+                color_grading: shader(COLOR_GRADING_FRAGMENT_SHADER),
+            },
+            width,
+            height,
+        })
+    }
+
+    /// Adds an effect to the end of the chain.
+    pub fn push(&mut self, effect: PostEffect) {
+        self.effects.push(effect);
+    }
+
+    /// Returns the effects in the order they are applied.
+    pub fn effects(&self) -> &[PostEffect] {
+        &self.effects
+    }
+
+    /// Returns the effects mutably, for changing their parameters, reordering or removing them.
+    pub fn effects_mut(&mut self) -> &mut Vec<PostEffect> {
+        &mut self.effects
+    }
+
+    /// Resizes the internal render textures, for example after the window has been resized.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.scene.resize(width, height);
+        for render_texture in &mut self.ping_pong {
+            render_texture.resize(width, height);
+        }
+        self.bloom_chain.clear();
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Returns the HDR render texture (with depth) that the scene is rendered into between
+    /// [`PostProcessStack::begin`] and [`PostProcessStack::end`].
+    pub fn scene(&self) -> &RenderTexture {
+        &self.scene
+    }
+
+    /// Binds the scene render texture, so everything drawn until [`PostProcessStack::end`] is post processed.
+    pub fn begin(&self) {
+        self.scene.bind();
+    }
+
+    /// Unbinds the scene render texture and draws it through all effects into the framebuffer that was bound before.
+    /// Fails if the render textures needed by a bloom effect can't be created.
+    pub fn end(&mut self) -> Result<(), String> {
+        self.scene.unbind();
+        self.prepare_bloom_chain()?;
+        self.run(self.scene.texture());
+        Ok(())
+    }
+
+    /// Draws any texture through all effects into the currently bound framebuffer and viewport.
+    pub fn apply(&mut self, input: &Texture) -> Result<(), String> {
+        self.prepare_bloom_chain()?;
+        self.run(input);
+        Ok(())
+    }
+
+    /// Creates the half-sized bloom render textures needed by the longest bloom effect.
+    fn prepare_bloom_chain(&mut self) -> Result<(), String> {
+        let iterations = self
+            .effects
+            .iter()
+            .filter_map(|effect| match effect {
+                PostEffect::Bloom { iterations, .. } => Some((*iterations).max(1)),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0) as usize;
+
+        let mut width = self.width >> self.bloom_chain.len().min(31);
+        let mut height = self.height >> self.bloom_chain.len().min(31);
+        while self.bloom_chain.len() < iterations && width > 1 && height > 1 {
+            width = (width / 2).max(1);
+            height = (height / 2).max(1);
+            self.bloom_chain
+                .push(RenderTexture::new_with_format(width, height, TextureFormat::Rgba16F, false)?);
+        }
+
+        Ok(())
+    }
+
+    fn run(&self, input: &Texture) {
+        unsafe {
+            let mut target_framebuffer = 0;
+            let mut target_viewport = [0; 4];
+            gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut target_framebuffer);
+            gl::GetIntegerv(gl::VIEWPORT, target_viewport.as_mut_ptr());
+            let depth_test = gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE;
+            let blend = gl::IsEnabled(gl::BLEND) == gl::TRUE;
+            let mut polygon_mode = [gl::FILL as GLint; 2];
+            gl::GetIntegerv(gl::POLYGON_MODE, polygon_mode.as_mut_ptr());
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Disable(gl::BLEND);
+            gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL);
+
+            let bind_target = |index: usize, last: bool| {
+                if last {
+                    gl::BindFramebuffer(gl::FRAMEBUFFER, target_framebuffer as GLuint);
+                    let [x, y, width, height] = target_viewport;
+                    gl::Viewport(x, y, width, height);
+                } else {
+                    bind_render_texture(&self.ping_pong[index % 2]);
+                }
+            };
+
+            if self.effects.is_empty() {
+                bind_target(0, true);
+                self.draw(&self.shaders.copy, input, |_| {});
+            }
+
+            let mut source = input;
+            for (index, effect) in self.effects.iter().enumerate() {
+                let last = index + 1 == self.effects.len();
+
+                match effect {
+                    PostEffect::Fxaa => {
+                        bind_target(index, last);
+                        self.draw(&self.shaders.fxaa, source, |_| {});
+                    }
+                    PostEffect::Bloom { threshold, intensity, iterations } => {
+                        let levels = (*iterations as usize).clamp(1, self.bloom_chain.len().max(1));
+                        let bloom = self.render_bloom(source, *threshold, levels);
+
+                        bind_target(index, last);
+                        self.draw(&self.shaders.bloom_composite, source, |shader| {
+                            if let Some(bloom) = bloom {
+                                bloom.bind(1);
+                                shader.set_uniform_texture("bloom", 1);
+                                shader.set_uniform_1f("intensity", *intensity);
+                            } else {
+                                shader.set_uniform_1f("intensity", 0.0);
+                            }
+                        });
+                    }
+                    PostEffect::Tonemap { tonemapper, exposure } => {
+                        bind_target(index, last);
+                        self.draw(&self.shaders.tonemap, source, |shader| {
+                            shader.set_uniform_1i("tonemapper", (*tonemapper == Tonemapper::Aces) as i32);
+                            shader.set_uniform_1f("exposure", *exposure);
+                        });
+                    }
+                    PostEffect::Gamma(gamma) => {
+                        bind_target(index, last);
+                        self.draw(&self.shaders.gamma, source, |shader| {
+                            shader.set_uniform_1f("gamma", *gamma);
+                        });
+                    }
+                    PostEffect::Vignette { intensity, radius } => {
+                        bind_target(index, last);
+                        self.draw(&self.shaders.vignette, source, |shader| {
+                            shader.set_uniform_1f("intensity", *intensity);
+                            shader.set_uniform_1f("radius", *radius);
+                        });
+                    }
+                    PostEffect::ChromaticAberration { strength } => {
+                        bind_target(index, last);
+                        self.draw(&self.shaders.chromatic_aberration, source, |shader| {
+                            shader.set_uniform_1f("strength", *strength);
+                        });
+                    }
+                    PostEffect::ColorGrading { lut, intensity } => {
+                        bind_target(index, last);
+                        self.draw(&self.shaders.color_grading, source, |shader| {
+                            lut.bind(1);
+                            shader.set_uniform_texture("lut", 1);
+                            shader.set_uniform_1f("lutSize", lut.width() as f32);
+                            shader.set_uniform_1f("intensity", *intensity);
+                        });
+                    }
+                    PostEffect::Custom(shader) => {
+                        bind_target(index, last);
+                        self.draw(shader, source, |_| {});
+                    }
+                }
+
+                source = self.ping_pong[index % 2].texture();
+            }
+
+            gl::ActiveTexture(gl::TEXTURE0);
+            if depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            }
+            if blend {
+                gl::Enable(gl::BLEND);
+            }
+            gl::PolygonMode(gl::FRONT_AND_BACK, polygon_mode[0] as GLenum);
+        }
+    }
+
+    /// Renders the bright parts of `source` blurred into the first bloom texture, returning it.
+    fn render_bloom(&self, source: &Texture, threshold: f32, levels: usize) -> Option<&Texture> {
+        let chain = &self.bloom_chain[..levels.min(self.bloom_chain.len())];
+        let first = chain.first()?;
+
+        unsafe {
+            bind_render_texture(first);
+            self.draw(&self.shaders.bloom_prefilter, source, |shader| {
+                shader.set_uniform_1f("threshold", threshold);
+            });
+
+            for pair in chain.windows(2) {
+                bind_render_texture(&pair[1]);
+                self.draw(&self.shaders.bloom_downsample, pair[0].texture(), |_| {});
+            }
+
+            // Each level is blurred up into the next larger one and added on top of it
+            let mut blend_func = [0; 4];
+            gl::GetIntegerv(gl::BLEND_SRC_RGB, &mut blend_func[0]);
+            gl::GetIntegerv(gl::BLEND_DST_RGB, &mut blend_func[1]);
+            gl::GetIntegerv(gl::BLEND_SRC_ALPHA, &mut blend_func[2]);
+            gl::GetIntegerv(gl::BLEND_DST_ALPHA, &mut blend_func[3]);
+
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::ONE, gl::ONE);
+            for pair in chain.windows(2).rev() {
+                bind_render_texture(&pair[0]);
+                self.draw(&self.shaders.bloom_upsample, pair[1].texture(), |_| {});
+            }
+            gl::Disable(gl::BLEND);
+
+            let [src_rgb, dst_rgb, src_alpha, dst_alpha] = blend_func.map(|factor| factor as GLenum);
+            gl::BlendFuncSeparate(src_rgb, dst_rgb, src_alpha, dst_alpha);
+        }
+
+        Some(first.texture())
+    }
+
+    /// Draws the fullscreen triangle with `shader`, reading from `source` on texture unit 0.
+    fn draw<F: FnOnce(&Shader)>(&self, shader: &Shader, source: &Texture, set_uniforms: F) {
+        shader.bind_program();
+        source.bind(0);
+        shader.set_uniform_texture("source", 0);
+        shader.set_uniform_2f("texelSize", 1.0 / source.width() as f32, 1.0 / source.height() as f32);
+        set_uniforms(shader);
+
+        unsafe {
+            self.triangle.bind();
+            gl::DrawElements(
+                gl::TRIANGLES,
+                self.triangle.indices_length as i32,
+                gl::UNSIGNED_INT,
+                ptr::null(),
+            );
+            self.triangle.unbind();
+        }
+
+        shader.unbind_program();
+    }
+}
+
+unsafe fn bind_render_texture(render_texture: &RenderTexture) {
+    gl::BindFramebuffer(gl::FRAMEBUFFER, render_texture.framebuffer_id);
+    gl::Viewport(0, 0, render_texture.width() as i32, render_texture.height() as i32);
+}
+
+/// Creates a single triangle that covers the whole screen, with 2D positions at location 0.
+pub(crate) fn fullscreen_triangle() -> Mesh {
+    let vertices = [
+        -1.0, -1.0,
+         3.0, -1.0,
+        -1.0,  3.0,
+    ];
+
+    let mut mesh = Mesh::new();
+    mesh.update_vertices(&vertices);
+    mesh.update_indices(&[0, 1, 2]);
+    mesh.add_vertex_attributes(&[
+        (0, 2, gl::FLOAT, false), // position
+    ]);
+    mesh
+}
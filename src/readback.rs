@@ -0,0 +1,319 @@
+extern crate gl;
+
+use gl::types::*;
+use std::marker::PhantomData;
+use std::ptr;
+
+use crate::framebuffer::Rect;
+
+/// The channels read back by `read_pixels`. Depth and stencil can only be read from depth textures
+/// (or the depth buffer of a render texture or window), the others only from color buffers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    Red,
+    Rg,
+    Rgb,
+    Rgba,
+    /// Unnormalized integers, for integer textures like `TextureFormat::R32UI`.
+    RedInteger,
+    RgbaInteger,
+    Depth,
+    Stencil,
+}
+
+impl PixelFormat {
+    /// Returns the number of components per pixel.
+    pub fn components(&self) -> usize {
+        match self {
+            PixelFormat::Red | PixelFormat::RedInteger | PixelFormat::Depth | PixelFormat::Stencil => 1,
+            PixelFormat::Rg => 2,
+            PixelFormat::Rgb => 3,
+            PixelFormat::Rgba | PixelFormat::RgbaInteger => 4,
+        }
+    }
+
+    /// Returns true if this format reads from the depth or stencil buffer instead of a color buffer.
+    pub fn is_depth_or_stencil(&self) -> bool {
+        matches!(self, PixelFormat::Depth | PixelFormat::Stencil)
+    }
+}
+
+impl From<PixelFormat> for GLenum {
+    fn from(format: PixelFormat) -> Self {
+        match format {
+            PixelFormat::Red => gl::RED,
+            PixelFormat::Rg => gl::RG,
+            PixelFormat::Rgb => gl::RGB,
+            PixelFormat::Rgba => gl::RGBA,
+            PixelFormat::RedInteger => gl::RED_INTEGER,
+            PixelFormat::RgbaInteger => gl::RGBA_INTEGER,
+            PixelFormat::Depth => gl::DEPTH_COMPONENT,
+            PixelFormat::Stencil => gl::STENCIL_INDEX,
+        }
+    }
+}
+
+/// A component type pixels can be read back as. Normalized formats are converted by OpenGL,
+/// so reading an RGBA8 texture as `f32` returns values from 0 to 1.
+pub trait PixelComponent: Copy + Default {
+    /// The OpenGL type matching this Rust type.
+    const GL_TYPE: GLenum;
+}
+
+impl PixelComponent for u8 {
+    const GL_TYPE: GLenum = gl::UNSIGNED_BYTE;
+}
+
+impl PixelComponent for i8 {
+    const GL_TYPE: GLenum = gl::BYTE;
+}
+
+impl PixelComponent for u16 {
+    const GL_TYPE: GLenum = gl::UNSIGNED_SHORT;
+}
+
+impl PixelComponent for i16 {
+    const GL_TYPE: GLenum = gl::SHORT;
+}
+
+impl PixelComponent for u32 {
+    const GL_TYPE: GLenum = gl::UNSIGNED_INT;
+}
+
+impl PixelComponent for i32 {
+    const GL_TYPE: GLenum = gl::INT;
+}
+
+impl PixelComponent for f32 {
+    const GL_TYPE: GLenum = gl::FLOAT;
+}
+
+/// A pixel readback that is copied into a pixel pack buffer (PBO) by the GPU in the background,
+/// so reading doesn't stall rendering. Poll it on later frames until the data is available.
+pub struct PixelReadback<T: PixelComponent> {
+    buffer_id: GLuint,
+    fence: GLsync,
+    len: usize,
+    done: bool,
+    _component: PhantomData<T>,
+}
+
+impl<T: PixelComponent> PixelReadback<T> {
+    /// Starts copying a rectangle of a framebuffer into a new pixel pack buffer.
+    pub(crate) fn start(framebuffer_id: GLuint, read_buffer: Option<GLenum>, rect: Rect, format: PixelFormat) -> Self {
+        let len = rect.width as usize * rect.height as usize * format.components();
+        let mut buffer_id = 0;
+
+        let fence = with_read_framebuffer(framebuffer_id, read_buffer, || unsafe {
+            gl::GenBuffers(1, &mut buffer_id);
+            gl::BindBuffer(gl::PIXEL_PACK_BUFFER, buffer_id);
+            gl::BufferData(
+                gl::PIXEL_PACK_BUFFER,
+                (len * size_of::<T>()) as isize,
+                ptr::null(),
+                gl::STREAM_READ,
+            );
+
+            // With a pixel pack buffer bound, the data pointer is an offset into the buffer
+            read_pixels_into::<T>(rect, format, ptr::null_mut());
+
+            gl::BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+            gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0)
+        });
+
+        PixelReadback {
+            buffer_id,
+            fence,
+            len,
+            done: false,
+            _component: PhantomData,
+        }
+    }
+
+    /// Returns true once the GPU has finished copying the pixels.
+    pub fn is_ready(&self) -> bool {
+        if self.done {
+            return true;
+        }
+
+        let status = unsafe { gl::ClientWaitSync(self.fence, 0, 0) };
+        status == gl::ALREADY_SIGNALED || status == gl::CONDITION_SATISFIED
+    }
+
+    /// Returns the pixels if the GPU has finished copying them, otherwise `None` without waiting.
+    /// The pixels are only returned once, later calls return `None`. Fails if the buffer can not be mapped.
+    pub fn poll(&mut self) -> Option<Result<Vec<T>, String>> {
+        if self.done || !self.is_ready() {
+            return None;
+        }
+
+        Some(self.take())
+    }
+
+    /// Blocks until the GPU has finished copying the pixels and returns them. Fails if the buffer can not be mapped.
+    pub fn wait(mut self) -> Result<Vec<T>, String> {
+        if self.done {
+            return Ok(Vec::new());
+        }
+
+        unsafe {
+            // Flush on the first wait so the fence is guaranteed to be signaled eventually
+            let mut flags = gl::SYNC_FLUSH_COMMANDS_BIT;
+            loop {
+                let status = gl::ClientWaitSync(self.fence, flags, 1_000_000);
+                if status != gl::TIMEOUT_EXPIRED {
+                    break;
+                }
+                flags = 0;
+            }
+        }
+
+        self.take()
+    }
+
+    fn take(&mut self) -> Result<Vec<T>, String> {
+        self.done = true;
+        let mut pixels = vec![T::default(); self.len];
+
+        let unmapped = unsafe {
+            gl::BindBuffer(gl::PIXEL_PACK_BUFFER, self.buffer_id);
+            let mapped = gl::MapBufferRange(
+                gl::PIXEL_PACK_BUFFER,
+                0,
+                (self.len * size_of::<T>()) as isize,
+                gl::MAP_READ_BIT,
+            );
+            if mapped.is_null() {
+                gl::BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+                return Err("[FerrousGl Error] Failed to map the pixel readback buffer.".to_string());
+            }
+
+            ptr::copy_nonoverlapping(mapped as *const T, pixels.as_mut_ptr(), self.len);
+            let unmapped = gl::UnmapBuffer(gl::PIXEL_PACK_BUFFER);
+            gl::BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+            unmapped
+        };
+
+        // The buffer contents become undefined if the driver lost them while mapped
+        if unmapped == gl::FALSE {
+            return Err("[FerrousGl Error] The pixel readback buffer was corrupted while it was mapped.".to_string());
+        }
+
+        Ok(pixels)
+    }
+}
+
+impl<T: PixelComponent> Drop for PixelReadback<T> {
+    /// Cleans up the pixel pack buffer and fence.
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteSync(self.fence);
+            gl::DeleteBuffers(1, &self.buffer_id);
+        }
+    }
+}
+
+/// Reads a rectangle of a framebuffer into a new vector, blocking until the GPU has finished rendering.
+/// Rows start at the bottom of the rectangle.
+pub(crate) fn read_framebuffer<T: PixelComponent>(
+    framebuffer_id: GLuint,
+    read_buffer: Option<GLenum>,
+    rect: Rect,
+    format: PixelFormat,
+) -> Vec<T> {
+    let mut pixels = vec![T::default(); rect.width as usize * rect.height as usize * format.components()];
+
+    with_read_framebuffer(framebuffer_id, read_buffer, || unsafe {
+        read_pixels_into::<T>(rect, format, pixels.as_mut_ptr() as *mut GLvoid);
+    });
+
+    pixels
+}
+
+/// Binds a framebuffer for reading, optionally selecting its read buffer, runs `f` and then restores the previous state.
+pub(crate) fn with_read_framebuffer<R, F: FnOnce() -> R>(framebuffer_id: GLuint, read_buffer: Option<GLenum>, f: F) -> R {
+    unsafe {
+        let mut previous_framebuffer = 0;
+        gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut previous_framebuffer);
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, framebuffer_id);
+
+        let mut previous_read_buffer = 0;
+        if let Some(read_buffer) = read_buffer {
+            gl::GetIntegerv(gl::READ_BUFFER, &mut previous_read_buffer);
+            gl::ReadBuffer(read_buffer);
+        }
+
+        let result = f();
+
+        if read_buffer.is_some() {
+            gl::ReadBuffer(previous_read_buffer as GLenum);
+        }
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, previous_framebuffer as GLuint);
+
+        result
+    }
+}
+
+/// Validates that a rectangle lies inside a surface of the given size.
+pub(crate) fn check_rect(rect: Rect, width: u32, height: u32) -> Result<(), String> {
+    if !fits_inside([rect.x, rect.y], [rect.width, rect.height], [width, height]) {
+        return Err(format!(
+            "[FerrousGl Error] Region {}x{} at ({}, {}) is outside of the {}x{} surface.",
+            rect.width, rect.height, rect.x, rect.y, width, height
+        ));
+    }
+
+    Ok(())
+}
+
+/// Returns true if the box starting at `offset` with the given `size` ends inside `limits` on every axis.
+/// Boxes whose end doesn't fit in a `u32` are outside.
+pub(crate) fn fits_inside<const N: usize>(offset: [u32; N], size: [u32; N], limits: [u32; N]) -> bool {
+    (0..N).all(|axis| {
+        offset[axis]
+            .checked_add(size[axis])
+            .is_some_and(|end| end <= limits[axis])
+    })
+}
+
+unsafe fn read_pixels_into<T: PixelComponent>(rect: Rect, format: PixelFormat, data: *mut GLvoid) {
+    gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+    gl::ReadPixels(
+        rect.x as i32,
+        rect.y as i32,
+        rect.width as i32,
+        rect.height as i32,
+        format.into(),
+        T::GL_TYPE,
+        data,
+    );
+    gl::PixelStorei(gl::PACK_ALIGNMENT, 4);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_rect_accepts_regions_inside_the_surface() {
+        assert!(check_rect(Rect { x: 0, y: 0, width: 64, height: 32 }, 64, 32).is_ok());
+        assert!(check_rect(Rect { x: 63, y: 31, width: 1, height: 1 }, 64, 32).is_ok());
+    }
+
+    #[test]
+    fn check_rect_rejects_regions_outside_the_surface() {
+        assert!(check_rect(Rect { x: 1, y: 0, width: 64, height: 32 }, 64, 32).is_err());
+        assert!(check_rect(Rect { x: 0, y: 0, width: 64, height: 33 }, 64, 32).is_err());
+        assert!(check_rect(Rect { x: u32::MAX, y: 0, width: 2, height: 1 }, 64, 32).is_err());
+        assert!(check_rect(Rect { x: 0, y: 1, width: 1, height: u32::MAX }, 64, 32).is_err());
+    }
+
+    #[test]
+    fn fits_inside_checks_every_axis() {
+        assert!(fits_inside([0, 0, 3], [4, 4, 1], [4, 4, 4]));
+        assert!(fits_inside([4], [0], [4]));
+        assert!(!fits_inside([0, 0, 4], [4, 4, 1], [4, 4, 4]));
+        assert!(!fits_inside([0, 0, u32::MAX], [4, 4, 1], [4, 4, 4]));
+        assert!(!fits_inside([1, 0], [u32::MAX, 1], [u32::MAX, 1]));
+    }
+}
@@ -1,45 +1,104 @@
-use gl::types::GLuint;
+use gl::types::{GLenum, GLuint};
+use glam::Vec4;
+use std::cell::Cell;
 
-use crate::Texture;
+use crate::framebuffer::{preserve_framebuffer_bindings, Rect};
+use crate::readback::{check_rect, read_framebuffer, PixelComponent, PixelFormat, PixelReadback};
+use crate::texture::{check_pixel_format, TextureFormat, WrapMode};
+use crate::{GlWindow, Texture};
 
 /// Represents a render texture, which allows rendering on. Can be used like a [`ferrousgl::texture::Texture`].
+/// A render texture can have several color textures (multiple render targets), for example a G-buffer.
 pub struct RenderTexture {
-    framebuffer_id: GLuint,
-    texture: Texture,
-    depth_texture: Option<Texture>, // Added depth texture
+    pub(crate) framebuffer_id: GLuint,
+    color_textures: Vec<Texture>,
+    depth_texture: Option<Texture>,
+    multisample: Option<MultisampleTarget>,
     width: u32,
     height: u32,
+    auto_resize_scale: Option<f32>,
+    previous_framebuffers: Cell<(GLuint, GLuint)>,
+    previous_viewport: Cell<Option<[i32; 4]>>,
+}
+
+/// The multisampled framebuffer that is rendered into before resolving into the color and depth textures.
+struct MultisampleTarget {
+    framebuffer_id: GLuint,
+    renderbuffers: Vec<(GLuint, TextureFormat)>,
+    samples: u32,
 }
 
 impl RenderTexture {
     /// Creates a new render texture with the specified width and height.
     /// Optionally creates a depth texture attachment if `with_depth` is true.
     pub fn new(width: u32, height: u32, with_depth: bool) -> Result<Self, String> {
-        let mut framebuffer_id = 0;
-        let texture = Texture::new_empty(width, height)?;
+        Self::new_with_format(width, height, TextureFormat::Rgba8, with_depth)
+    }
 
-        let depth_texture = if with_depth {
-            Some(Self::create_depth_texture(width, height)?)
+    /// Creates a new render texture whose color texture uses the given texture format, for example
+    /// `TextureFormat::Rgba16F` for HDR rendering. Optionally creates a depth texture attachment if `with_depth` is true.
+    pub fn new_with_format(
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+        with_depth: bool,
+    ) -> Result<Self, String> {
+        Self::new_with_config(RenderTextureConfig {
+            color_formats: vec![format],
+            depth_format: with_depth.then_some(TextureFormat::Depth24),
+            ..RenderTextureConfig::new(width, height)
+        })
+    }
+
+    /// Creates a new render texture from a config. Each color format adds a color texture, attached to
+    /// `COLOR_ATTACHMENT0` onwards and written by the fragment shader output with the same `location`.
+    pub fn new_with_config(config: RenderTextureConfig) -> Result<Self, String> {
+        config.validate(max_color_attachments())?;
+
+        let RenderTextureConfig {
+            width,
+            height,
+            color_formats,
+            depth_format,
+            samples,
+        } = config;
+
+        let multisample = if samples > 1 {
+            Some(MultisampleTarget::new(width, height, samples, &color_formats, depth_format)?)
         } else {
             None
         };
 
-        unsafe {
+        let mut framebuffer_id = 0;
+        let mut color_textures = Vec::with_capacity(color_formats.len());
+        for format in color_formats {
+            color_textures.push(Texture::new_empty_with_format(width, height, format)?);
+        }
+
+        let depth_texture = match depth_format {
+            Some(format) => Some(Self::create_depth_texture(width, height, format)?),
+            None => None,
+        };
+
+        preserve_framebuffer_bindings(|| unsafe {
             gl::GenFramebuffers(1, &mut framebuffer_id);
             gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer_id);
 
-            gl::FramebufferTexture2D(
-                gl::FRAMEBUFFER,
-                gl::COLOR_ATTACHMENT0,
-                gl::TEXTURE_2D,
-                texture.id,
-                0,
-            );
+            for (i, texture) in color_textures.iter().enumerate() {
+                gl::FramebufferTexture2D(
+                    gl::FRAMEBUFFER,
+                    gl::COLOR_ATTACHMENT0 + i as GLenum,
+                    gl::TEXTURE_2D,
+                    texture.id,
+                    0,
+                );
+            }
+            set_draw_buffers(color_textures.len());
 
             if let Some(ref depth) = depth_texture {
                 gl::FramebufferTexture2D(
                     gl::FRAMEBUFFER,
-                    gl::DEPTH_ATTACHMENT,
+                    depth_attachment(depth.format),
                     gl::TEXTURE_2D,
                     depth.id,
                     0,
@@ -47,89 +106,233 @@ impl RenderTexture {
             }
 
             if gl::CheckFramebufferStatus(gl::FRAMEBUFFER) != gl::FRAMEBUFFER_COMPLETE {
+                gl::DeleteFramebuffers(1, &framebuffer_id);
                 return Err("[FerrousGl Error] Framebuffer is not complete!".to_string());
             }
 
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-        }
+            Ok(())
+        })?;
 
         Ok(RenderTexture {
             framebuffer_id,
-            texture,
+            color_textures,
             depth_texture,
+            multisample,
             width,
             height,
+            auto_resize_scale: None,
+            previous_framebuffers: Cell::new((0, 0)),
+            previous_viewport: Cell::new(None),
         })
     }
 
-    /// Creates a depth texture with the specified dimensions
-    fn create_depth_texture(width: u32, height: u32) -> Result<Texture, String> {
-        let mut texture_id = 0;
+    /// Creates a depth texture with the specified dimensions and depth format
+    fn create_depth_texture(width: u32, height: u32, format: TextureFormat) -> Result<Texture, String> {
+        let texture = Texture::new_empty_with_format(width, height, format)?;
+        texture.set_wrap(WrapMode::ClampToBorder, WrapMode::ClampToBorder);
+        texture.set_border_color(Vec4::ONE);
 
-        unsafe {
-            gl::GenTextures(1, &mut texture_id);
-            gl::BindTexture(gl::TEXTURE_2D, texture_id);
-
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::DEPTH_COMPONENT as i32,
-                width as i32,
-                height as i32,
-                0,
-                gl::DEPTH_COMPONENT,
-                gl::UNSIGNED_INT,
-                std::ptr::null(),
-            );
-
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
-            gl::TexParameteri(
-                gl::TEXTURE_2D,
-                gl::TEXTURE_WRAP_S,
-                gl::CLAMP_TO_BORDER as i32,
-            );
-            gl::TexParameteri(
-                gl::TEXTURE_2D,
-                gl::TEXTURE_WRAP_T,
-                gl::CLAMP_TO_BORDER as i32,
-            );
-
-            let border_color = [1.0, 1.0, 1.0, 1.0];
-            gl::TexParameterfv(
-                gl::TEXTURE_2D,
-                gl::TEXTURE_BORDER_COLOR,
-                border_color.as_ptr(),
-            );
-
-            gl::BindTexture(gl::TEXTURE_2D, 0);
-        }
-
-        Ok(Texture {
-            id: texture_id,
-            width,
-            height,
-        })
+        Ok(texture)
     }
 
-    /// Binds the render texture as the current framebuffer.
+    /// Resizes all color and depth textures, keeping their formats and parameters. The previous contents are discarded.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let (width, height) = (width.max(1), height.max(1));
+        if width == self.width && height == self.height {
+            return;
+        }
+
+        for texture in self.color_textures.iter_mut().chain(self.depth_texture.as_mut()) {
+            texture.resize(width, height);
+        }
+
+        if let Some(multisample) = &self.multisample {
+            multisample.resize(width, height);
+        }
+
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Makes the render texture follow the size of the window's framebuffer, multiplied by `scale`
+    /// (for example 0.5 to render at half resolution). `None` turns auto-resizing off.
+    /// The size is updated by [`RenderTexture::update_size`].
+    pub fn set_auto_resize(&mut self, scale: Option<f32>) {
+        self.auto_resize_scale = scale;
+    }
+
+    /// Resizes the render texture to match the window if auto-resizing is enabled, see [`RenderTexture::set_auto_resize`].
+    /// Call this once per frame before binding. Returns true if the render texture has been resized.
+    pub fn update_size(&mut self, window: &GlWindow) -> bool {
+        let Some(scale) = self.auto_resize_scale else {
+            return false;
+        };
+
+        let (framebuffer_width, framebuffer_height) = window.get_framebuffer_size();
+        let width = (framebuffer_width as f32 * scale).round() as u32;
+        let height = (framebuffer_height as f32 * scale).round() as u32;
+
+        let previous_size = (self.width, self.height);
+        self.resize(width, height);
+        previous_size != (self.width, self.height)
+    }
+
+    /// Binds the render texture as the current framebuffer and sets the viewport to its size.
+    /// The previously bound read and draw framebuffers and viewport are restored by [`RenderTexture::unbind`].
+    /// Multisampled render textures bind their multisampled framebuffer, call [`RenderTexture::resolve`] after rendering.
     pub fn bind(&self) {
         unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer_id);
+            let mut previous_read = 0;
+            let mut previous_draw = 0;
+            let mut previous_viewport = [0; 4];
+            gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut previous_read);
+            gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut previous_draw);
+            gl::GetIntegerv(gl::VIEWPORT, previous_viewport.as_mut_ptr());
+            self.previous_framebuffers.set((previous_read as GLuint, previous_draw as GLuint));
+            self.previous_viewport.set(Some(previous_viewport));
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.draw_framebuffer_id());
             gl::Viewport(0, 0, self.width as i32, self.height as i32);
         }
     }
 
-    /// Unbinds the render texture (binds the default framebuffer), after this normal rendering can continue.
+    /// Unbinds the render texture, restoring the read and draw framebuffers and viewport that were active when it was bound.
+    /// After this normal rendering can continue.
     pub fn unbind(&self) {
         unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            let (previous_read, previous_draw) = self.previous_framebuffers.replace((0, 0));
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, previous_read);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, previous_draw);
+
+            if let Some([x, y, width, height]) = self.previous_viewport.take() {
+                gl::Viewport(x, y, width, height);
+            }
         }
     }
 
-    /// Returns a reference to the color texture that this render texture renders to.
+    /// Returns the width of the render texture.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of the render texture.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Resolves the multisampled rendering into the color and depth textures, so they can be sampled by shaders.
+    /// Does nothing if the render texture is not multisampled.
+    pub fn resolve(&self) {
+        let Some(multisample) = &self.multisample else {
+            return;
+        };
+
+        unsafe {
+            let mut previous_read = 0;
+            let mut previous_draw = 0;
+            gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut previous_read);
+            gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut previous_draw);
+
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, multisample.framebuffer_id);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, self.framebuffer_id);
+
+            let (width, height) = (self.width as i32, self.height as i32);
+
+            // Each color attachment has to be blitted on its own
+            for i in 0..self.color_textures.len() {
+                let attachment = gl::COLOR_ATTACHMENT0 + i as GLenum;
+                gl::ReadBuffer(attachment);
+                gl::DrawBuffer(attachment);
+                gl::BlitFramebuffer(0, 0, width, height, 0, 0, width, height, gl::COLOR_BUFFER_BIT, gl::NEAREST);
+            }
+
+            if let Some(depth) = &self.depth_texture {
+                let mask = if depth.format.has_stencil() {
+                    gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT
+                } else {
+                    gl::DEPTH_BUFFER_BIT
+                };
+                gl::BlitFramebuffer(0, 0, width, height, 0, 0, width, height, mask, gl::NEAREST);
+            }
+
+            // Restore the draw buffers of the resolved framebuffer and the read buffer of the multisampled one
+            if !self.color_textures.is_empty() {
+                let draw_buffers = color_attachments(self.color_textures.len());
+                gl::DrawBuffers(draw_buffers.len() as i32, draw_buffers.as_ptr());
+                gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+            }
+
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, previous_read as GLuint);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, previous_draw as GLuint);
+        }
+    }
+
+    /// Reads a rectangle of the first color texture, or of the depth texture for `PixelFormat::Depth` and
+    /// `PixelFormat::Stencil`, blocking until rendering has finished. Rows start at the bottom of the rectangle.
+    /// Multisampled render textures have to be resolved first.
+    pub fn read_pixels<T: PixelComponent>(&self, rect: Rect, format: PixelFormat) -> Result<Vec<T>, String> {
+        let read_buffer = self.check_readable(rect, format)?;
+        Ok(read_framebuffer(self.framebuffer_id, read_buffer, rect, format))
+    }
+
+    /// Starts reading a rectangle in the background, see [`RenderTexture::read_pixels`] and [`PixelReadback`].
+    pub fn read_pixels_async<T: PixelComponent>(&self, rect: Rect, format: PixelFormat) -> Result<PixelReadback<T>, String> {
+        let read_buffer = self.check_readable(rect, format)?;
+        Ok(PixelReadback::start(self.framebuffer_id, read_buffer, rect, format))
+    }
+
+    /// Checks that the render texture has a texture to read the format from, returning the read buffer to use.
+    pub(crate) fn check_readable(&self, rect: Rect, format: PixelFormat) -> Result<Option<GLenum>, String> {
+        check_rect(rect, self.width, self.height)?;
+
+        if format.is_depth_or_stencil() {
+            let depth = self
+                .depth_texture
+                .as_ref()
+                .ok_or("[FerrousGl Error] The render texture has no depth texture to read from.")?;
+            check_pixel_format(depth.format, format)?;
+            Ok(None)
+        } else {
+            let color = self
+                .color_textures
+                .first()
+                .ok_or("[FerrousGl Error] The render texture has no color texture to read from.")?;
+            check_pixel_format(color.format, format)?;
+            Ok(Some(gl::COLOR_ATTACHMENT0))
+        }
+    }
+
+    /// Returns the number of samples per pixel, or 1 if the render texture is not multisampled.
+    pub fn samples(&self) -> u32 {
+        self.multisample.as_ref().map_or(1, |multisample| multisample.samples)
+    }
+
+    /// Returns the framebuffer that rendering goes into, the multisampled one if it exists.
+    pub(crate) fn draw_framebuffer_id(&self) -> GLuint {
+        self.multisample
+            .as_ref()
+            .map_or(self.framebuffer_id, |multisample| multisample.framebuffer_id)
+    }
+
+    /// Returns a reference to the (first) color texture that this render texture renders to.
+    /// Panics if the render texture has no color textures.
     pub fn texture(&self) -> &Texture {
-        &self.texture
+        &self.color_textures[0]
+    }
+
+    /// Returns a reference to the color texture attached to `COLOR_ATTACHMENT0 + index`, if it exists.
+    pub fn color_texture(&self, index: usize) -> Option<&Texture> {
+        self.color_textures.get(index)
+    }
+
+    /// Returns all color textures, in attachment order.
+    pub fn color_textures(&self) -> &[Texture] {
+        &self.color_textures
+    }
+
+    /// Returns true if the depth texture also contains a stencil buffer.
+    pub fn has_stencil(&self) -> bool {
+        self.depth_texture.as_ref().is_some_and(|depth| depth.format.has_stencil())
     }
 
     /// Returns a reference to the depth texture if it exists.
@@ -138,6 +341,104 @@ impl RenderTexture {
     }
 }
 
+impl MultisampleTarget {
+    /// Creates a framebuffer with multisampled renderbuffers matching the color and depth formats.
+    fn new(
+        width: u32,
+        height: u32,
+        samples: u32,
+        color_formats: &[TextureFormat],
+        depth_format: Option<TextureFormat>,
+    ) -> Result<Self, String> {
+        // Integer formats often support fewer samples, and every attachment has to use the same count
+        let mut max_samples = 0;
+        unsafe {
+            gl::GetIntegerv(gl::MAX_SAMPLES, &mut max_samples);
+            if color_formats.iter().any(|format| format.is_integer()) {
+                let mut max_integer_samples = 0;
+                gl::GetIntegerv(gl::MAX_INTEGER_SAMPLES, &mut max_integer_samples);
+                max_samples = max_samples.min(max_integer_samples);
+            }
+        }
+        let samples = samples.min(max_samples.max(1) as u32);
+
+        let mut framebuffer_id = 0;
+        let mut renderbuffers = Vec::new();
+
+        preserve_framebuffer_bindings(|| unsafe {
+            gl::GenFramebuffers(1, &mut framebuffer_id);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer_id);
+
+            let attachments = color_formats
+                .iter()
+                .enumerate()
+                .map(|(i, format)| (gl::COLOR_ATTACHMENT0 + i as GLenum, *format))
+                .chain(depth_format.map(|format| (depth_attachment(format), format)));
+
+            for (attachment, format) in attachments {
+                let mut renderbuffer = 0;
+                gl::GenRenderbuffers(1, &mut renderbuffer);
+                gl::BindRenderbuffer(gl::RENDERBUFFER, renderbuffer);
+                gl::RenderbufferStorageMultisample(
+                    gl::RENDERBUFFER,
+                    samples as i32,
+                    format.internal_format(),
+                    width as i32,
+                    height as i32,
+                );
+                gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, attachment, gl::RENDERBUFFER, renderbuffer);
+                renderbuffers.push((renderbuffer, format));
+            }
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+
+            set_draw_buffers(color_formats.len());
+
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+
+            let target = MultisampleTarget {
+                framebuffer_id,
+                renderbuffers,
+                samples,
+            };
+
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                return Err("[FerrousGl Error] Multisampled framebuffer is not complete!".to_string());
+            }
+
+            Ok(target)
+        })
+    }
+
+    /// Reallocates every renderbuffer with a new size, they stay attached to the framebuffer.
+    fn resize(&self, width: u32, height: u32) {
+        unsafe {
+            for &(renderbuffer, format) in &self.renderbuffers {
+                gl::BindRenderbuffer(gl::RENDERBUFFER, renderbuffer);
+                gl::RenderbufferStorageMultisample(
+                    gl::RENDERBUFFER,
+                    self.samples as i32,
+                    format.internal_format(),
+                    width as i32,
+                    height as i32,
+                );
+            }
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+        }
+    }
+}
+
+impl Drop for MultisampleTarget {
+    /// Cleans up the multisampled framebuffer and its renderbuffers.
+    fn drop(&mut self) {
+        unsafe {
+            for (renderbuffer, _) in &self.renderbuffers {
+                gl::DeleteRenderbuffers(1, renderbuffer);
+            }
+            gl::DeleteFramebuffers(1, &self.framebuffer_id);
+        }
+    }
+}
+
 impl Drop for RenderTexture {
     /// Cleans up the framebuffer when it goes out of scope.
     fn drop(&mut self) {
@@ -146,3 +447,155 @@ impl Drop for RenderTexture {
         }
     }
 }
+
+/// Struct to more easily configure render textures with several color textures, see [`RenderTexture::new_with_config`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderTextureConfig {
+    pub width: u32,
+    pub height: u32,
+    /// The format of each color texture, may be empty for depth only rendering.
+    pub color_formats: Vec<TextureFormat>,
+    /// The format of the depth texture, or `None` for no depth texture.
+    /// Use `TextureFormat::Depth24Stencil8` or `TextureFormat::Depth32FStencil8` to also get a stencil buffer.
+    pub depth_format: Option<TextureFormat>,
+    /// The number of samples per pixel for multisample anti-aliasing, 0 or 1 disables it.
+    /// Multisampled rendering has to be resolved with [`RenderTexture::resolve`] before the textures are sampled.
+    /// The count is clamped to `GL_MAX_SAMPLES`, or `GL_MAX_INTEGER_SAMPLES` with integer color formats.
+    pub samples: u32,
+}
+
+impl RenderTextureConfig {
+    /// Creates a config for a render texture of the given size with a single RGBA8 color texture,
+    /// no depth texture and no multisampling.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            color_formats: vec![TextureFormat::Rgba8],
+            depth_format: None,
+            samples: 0,
+        }
+    }
+
+    /// Checks the size and formats, given how many color textures the driver can attach.
+    fn validate(&self, max_attachments: usize) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 {
+            return Err(format!(
+                "[FerrousGl Error] A render texture can not be {}x{}, both sides have to be at least 1.",
+                self.width, self.height
+            ));
+        }
+
+        if self.color_formats.iter().any(|format| format.is_depth()) {
+            return Err("[FerrousGl Error] The color textures of a render texture can not use a depth format!".to_string());
+        }
+
+        if self.depth_format.is_some_and(|format| !format.is_depth()) {
+            return Err("[FerrousGl Error] The depth texture of a render texture has to use a depth format!".to_string());
+        }
+
+        if self.color_formats.len() > max_attachments {
+            return Err(format!(
+                "[FerrousGl Error] A render texture can have at most {} color textures on this driver, but {} were requested.",
+                max_attachments,
+                self.color_formats.len()
+            ));
+        }
+
+        if self.color_formats.is_empty() && self.depth_format.is_none() {
+            return Err("[FerrousGl Error] A render texture needs at least one color or depth texture!".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns how many color textures can be attached to a framebuffer.
+fn max_color_attachments() -> usize {
+    let mut max_attachments = 0;
+    unsafe {
+        gl::GetIntegerv(gl::MAX_COLOR_ATTACHMENTS, &mut max_attachments);
+    }
+    max_attachments.max(1) as usize
+}
+
+/// Depth-stencil formats have to be attached to both the depth and the stencil attachment point.
+fn depth_attachment(format: TextureFormat) -> GLenum {
+    if format.has_stencil() {
+        gl::DEPTH_STENCIL_ATTACHMENT
+    } else {
+        gl::DEPTH_ATTACHMENT
+    }
+}
+
+fn color_attachments(count: usize) -> Vec<GLenum> {
+    (0..count).map(|i| gl::COLOR_ATTACHMENT0 + i as GLenum).collect()
+}
+
+/// Sets the draw buffers of the bound framebuffer to its color attachments, or none for depth only rendering.
+unsafe fn set_draw_buffers(count: usize) {
+    if count == 0 {
+        gl::DrawBuffer(gl::NONE);
+        gl::ReadBuffer(gl::NONE);
+    } else {
+        let draw_buffers = color_attachments(count);
+        gl::DrawBuffers(draw_buffers.len() as i32, draw_buffers.as_ptr());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_config_has_one_color_texture() {
+        let config = RenderTextureConfig::new(320, 240);
+        assert_eq!((config.width, config.height), (320, 240));
+        assert_eq!(config.color_formats, [TextureFormat::Rgba8]);
+        assert_eq!(config.depth_format, None);
+        assert_eq!(config.validate(8), Ok(()));
+    }
+
+    #[test]
+    fn validate_accepts_depth_only_and_multiple_targets() {
+        let depth_only = RenderTextureConfig {
+            color_formats: Vec::new(),
+            depth_format: Some(TextureFormat::Depth32F),
+            ..RenderTextureConfig::new(64, 64)
+        };
+        assert_eq!(depth_only.validate(1), Ok(()));
+
+        let g_buffer = RenderTextureConfig {
+            color_formats: vec![TextureFormat::Rgba16F, TextureFormat::Rgba8, TextureFormat::R32UI],
+            depth_format: Some(TextureFormat::Depth24Stencil8),
+            samples: 4,
+            ..RenderTextureConfig::new(64, 64)
+        };
+        assert_eq!(g_buffer.validate(3), Ok(()));
+        assert!(g_buffer.validate(2).is_err());
+    }
+
+    #[test]
+    fn validate_rejects_invalid_configs() {
+        let invalid = [
+            RenderTextureConfig::new(0, 64),
+            RenderTextureConfig::new(64, 0),
+            RenderTextureConfig {
+                color_formats: vec![TextureFormat::Depth24],
+                ..RenderTextureConfig::new(64, 64)
+            },
+            RenderTextureConfig {
+                depth_format: Some(TextureFormat::Rgba8),
+                ..RenderTextureConfig::new(64, 64)
+            },
+            RenderTextureConfig {
+                color_formats: Vec::new(),
+                ..RenderTextureConfig::new(64, 64)
+            },
+        ];
+
+        for config in invalid {
+            assert!(config.validate(8).is_err(), "{:?}", config);
+        }
+    }
+}
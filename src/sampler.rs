@@ -0,0 +1,142 @@
+extern crate gl;
+
+use gl::types::*;
+use glam::Vec4;
+
+use crate::texture::{max_anisotropy, min_filter, FilterMode, MipmapType, WrapMode, TEXTURE_MAX_ANISOTROPY};
+use crate::DepthType;
+
+/// Represents an OpenGL sampler object. A sampler bound to a texture unit overrides the filtering, wrapping and
+/// comparison settings of whatever texture is bound to that unit, without changing the texture itself.
+/// This allows sampling the same texture in different ways, like a shadow map with and without depth comparison.
+pub struct Sampler {
+    id: GLuint,
+}
+
+impl Sampler {
+    /// Creates a new sampler with linear filtering, no mipmaps and repeating wrap mode.
+    pub fn new() -> Self {
+        let mut sampler_id = 0;
+
+        unsafe {
+            gl::GenSamplers(1, &mut sampler_id);
+            gl::SamplerParameteri(sampler_id, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::SamplerParameteri(sampler_id, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::SamplerParameteri(sampler_id, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
+            gl::SamplerParameteri(sampler_id, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
+            gl::SamplerParameteri(sampler_id, gl::TEXTURE_WRAP_R, gl::REPEAT as i32);
+        }
+
+        Sampler { id: sampler_id }
+    }
+
+    /// Sets the preferred Texture Mipmap Type or Texture Filtering Mode such as None, Linear and Nearest.
+    pub fn set_mipmap_and_filtering(&self, mipmap_type: MipmapType, base_filter: FilterMode) {
+        unsafe {
+            gl::SamplerParameteri(
+                self.id,
+                gl::TEXTURE_MIN_FILTER,
+                min_filter(mipmap_type, base_filter) as i32,
+            );
+            gl::SamplerParameteri(
+                self.id,
+                gl::TEXTURE_MAG_FILTER,
+                GLenum::from(base_filter) as i32,
+            );
+        }
+    }
+
+    /// Sets how texture coordinates outside of the 0 to 1 range are handled, horizontally (s) and vertically (t).
+    pub fn set_wrap(&self, wrap_s: WrapMode, wrap_t: WrapMode) {
+        unsafe {
+            gl::SamplerParameteri(self.id, gl::TEXTURE_WRAP_S, GLenum::from(wrap_s) as i32);
+            gl::SamplerParameteri(self.id, gl::TEXTURE_WRAP_T, GLenum::from(wrap_t) as i32);
+        }
+    }
+
+    /// Sets the color that is sampled outside of the texture when using `WrapMode::ClampToBorder`.
+    pub fn set_border_color(&self, color: Vec4) {
+        unsafe {
+            gl::SamplerParameterfv(self.id, gl::TEXTURE_BORDER_COLOR, color.to_array().as_ptr());
+        }
+    }
+
+    /// Sets the anisotropic filtering level, clamped to what the driver supports.
+    /// Does nothing if the driver does not support anisotropic filtering.
+    pub fn set_anisotropy(&self, level: f32) {
+        let max_level = max_anisotropy();
+        if max_level <= 1.0 {
+            return;
+        }
+
+        unsafe {
+            gl::SamplerParameterf(self.id, TEXTURE_MAX_ANISOTROPY, level.clamp(1.0, max_level));
+        }
+    }
+
+    /// Sets the level of detail bias. Positive values make the texture blurrier, negative values make it sharper.
+    pub fn set_lod_bias(&self, bias: f32) {
+        unsafe {
+            gl::SamplerParameterf(self.id, gl::TEXTURE_LOD_BIAS, bias);
+        }
+    }
+
+    /// Clamps the level of detail that is used for sampling to the given range.
+    pub fn set_lod_range(&self, min_lod: f32, max_lod: f32) {
+        unsafe {
+            gl::SamplerParameterf(self.id, gl::TEXTURE_MIN_LOD, min_lod);
+            gl::SamplerParameterf(self.id, gl::TEXTURE_MAX_LOD, max_lod);
+        }
+    }
+
+    /// Sets the depth comparison function used when sampling a depth texture through a `sampler2DShadow`.
+    /// `DepthType::None` disables comparison, so the raw depth values are read instead.
+    pub fn set_compare_func(&self, compare_func: DepthType) {
+        let compare_func: Option<GLenum> = compare_func.into();
+
+        unsafe {
+            match compare_func {
+                Some(gl_func) => {
+                    gl::SamplerParameteri(
+                        self.id,
+                        gl::TEXTURE_COMPARE_MODE,
+                        gl::COMPARE_REF_TO_TEXTURE as i32,
+                    );
+                    gl::SamplerParameteri(self.id, gl::TEXTURE_COMPARE_FUNC, gl_func as i32);
+                }
+                None => {
+                    gl::SamplerParameteri(self.id, gl::TEXTURE_COMPARE_MODE, gl::NONE as i32);
+                }
+            }
+        }
+    }
+
+    /// Binds the sampler to a texture unit. The texture bound to the same unit will be sampled using this sampler.
+    pub fn bind(&self, texture_unit: u32) {
+        unsafe {
+            gl::BindSampler(texture_unit, self.id);
+        }
+    }
+
+    /// Unbinds the sampler from a texture unit, so the texture's own parameters are used again.
+    pub fn unbind(&self, texture_unit: u32) {
+        unsafe {
+            gl::BindSampler(texture_unit, 0);
+        }
+    }
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Sampler {
+    /// Cleans up the sampler when it goes out of scope.
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteSamplers(1, &self.id);
+        }
+    }
+}
@@ -0,0 +1,472 @@
+extern crate glam;
+
+use glam::{Mat4, Quat, Vec3};
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::{Material, Mesh};
+
+/// A position, rotation and scale, applied in the order scale, rotation, translation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Transform {
+    /// A transform that leaves everything unchanged.
+    pub const IDENTITY: Self = Transform {
+        translation: Vec3::ZERO,
+        rotation: Quat::IDENTITY,
+        scale: Vec3::ONE,
+    };
+
+    /// Creates a transform that only moves.
+    pub fn from_translation(translation: Vec3) -> Self {
+        Transform {
+            translation,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Returns the transform with its rotation replaced.
+    pub fn with_rotation(mut self, rotation: Quat) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Returns the transform with its scale replaced.
+    pub fn with_scale(mut self, scale: Vec3) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Returns the transform as a matrix.
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+
+    /// Creates a transform from a matrix without shear.
+    pub fn from_matrix(matrix: Mat4) -> Self {
+        let (scale, rotation, translation) = matrix.to_scale_rotation_translation();
+        Transform {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// A handle to a node of a [`Scene`]. Handles of removed nodes stay invalid even if their slot is reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId {
+    index: usize,
+    generation: u32,
+}
+
+struct SceneNode {
+    name: String,
+    transform: Transform,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    mesh: Option<Rc<Mesh>>,
+    material: Option<Rc<Material>>,
+    visible: bool,
+    world_matrix: Cell<Mat4>,
+    world_dirty: Cell<bool>,
+}
+
+struct NodeSlot {
+    generation: u32,
+    node: Option<SceneNode>,
+}
+
+/// A hierarchy of nodes with local transforms. World matrices are cached and only recomputed after a node
+/// or one of its parents moved. Nodes with a mesh and material are drawn by [`GlWindow::render`](crate::GlWindow::render).
+#[derive(Default)]
+pub struct Scene {
+    slots: Vec<NodeSlot>,
+    free_slots: Vec<usize>,
+    roots: Vec<NodeId>,
+}
+
+impl Scene {
+    /// Creates an empty scene.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an empty node with the given local transform, either at the top level or as a child of `parent`.
+    pub fn add_node(&mut self, name: &str, transform: Transform, parent: Option<NodeId>) -> NodeId {
+        if let Some(parent) = parent {
+            self.node(parent);
+        }
+
+        let node = SceneNode {
+            name: name.to_string(),
+            transform,
+            parent,
+            children: Vec::new(),
+            mesh: None,
+            material: None,
+            visible: true,
+            world_matrix: Cell::new(Mat4::IDENTITY),
+            world_dirty: Cell::new(true),
+        };
+
+        let id = match self.free_slots.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index];
+                slot.node = Some(node);
+                NodeId {
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                self.slots.push(NodeSlot {
+                    generation: 0,
+                    node: Some(node),
+                });
+                NodeId {
+                    index: self.slots.len() - 1,
+                    generation: 0,
+                }
+            }
+        };
+
+        match parent {
+            Some(parent) => self.node_mut(parent).children.push(id),
+            None => self.roots.push(id),
+        }
+
+        id
+    }
+
+    /// Adds a node that draws a mesh with a material.
+    pub fn add_mesh_node(
+        &mut self,
+        name: &str,
+        transform: Transform,
+        parent: Option<NodeId>,
+        mesh: Rc<Mesh>,
+        material: Rc<Material>,
+    ) -> NodeId {
+        let id = self.add_node(name, transform, parent);
+        let node = self.node_mut(id);
+        node.mesh = Some(mesh);
+        node.material = Some(material);
+        id
+    }
+
+    /// Removes a node together with all of its children.
+    pub fn remove_node(&mut self, id: NodeId) {
+        match self.node(id).parent {
+            Some(parent) => self.node_mut(parent).children.retain(|child| *child != id),
+            None => self.roots.retain(|root| *root != id),
+        }
+
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            let slot = &mut self.slots[id.index];
+            if let Some(node) = slot.node.take() {
+                stack.extend(node.children);
+            }
+            slot.generation = slot.generation.wrapping_add(1);
+            self.free_slots.push(id.index);
+        }
+    }
+
+    /// Returns true if the handle refers to a node that has not been removed.
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.slots
+            .get(id.index)
+            .is_some_and(|slot| slot.generation == id.generation && slot.node.is_some())
+    }
+
+    /// Returns the first node with the given name.
+    pub fn find(&self, name: &str) -> Option<NodeId> {
+        self.nodes().find(|id| self.node(*id).name == name)
+    }
+
+    /// Returns the handles of all nodes in the scene, parents before their children.
+    pub fn nodes(&self) -> impl Iterator<Item = NodeId> + '_ {
+        let mut stack: Vec<NodeId> = self.roots.iter().rev().copied().collect();
+        std::iter::from_fn(move || {
+            let id = stack.pop()?;
+            stack.extend(self.node(id).children.iter().rev().copied());
+            Some(id)
+        })
+    }
+
+    /// Returns the top level nodes.
+    pub fn roots(&self) -> &[NodeId] {
+        &self.roots
+    }
+
+    /// Returns the name of a node.
+    pub fn name(&self, id: NodeId) -> &str {
+        &self.node(id).name
+    }
+
+    /// Returns the parent of a node, or `None` for top level nodes.
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.node(id).parent
+    }
+
+    /// Returns the children of a node.
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.node(id).children
+    }
+
+    /// Moves a node and its children under a new parent, or to the top level with `None`.
+    /// The local transform is kept, so the node moves along with its new parent.
+    pub fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) -> Result<(), String> {
+        let mut ancestor = parent;
+        while let Some(current) = ancestor {
+            if current == id {
+                return Err(format!(
+                    "[FerrousGl Error] Node '{}' can not be a child of itself or one of its children.",
+                    self.node(id).name
+                ));
+            }
+            ancestor = self.node(current).parent;
+        }
+
+        match self.node(id).parent {
+            Some(old_parent) => self.node_mut(old_parent).children.retain(|child| *child != id),
+            None => self.roots.retain(|root| *root != id),
+        }
+        match parent {
+            Some(parent) => self.node_mut(parent).children.push(id),
+            None => self.roots.push(id),
+        }
+
+        self.node_mut(id).parent = parent;
+        self.mark_dirty(id);
+        Ok(())
+    }
+
+    /// Returns the local transform of a node, relative to its parent.
+    pub fn transform(&self, id: NodeId) -> &Transform {
+        &self.node(id).transform
+    }
+
+    /// Returns the local transform of a node for changing it. The world matrices of the node and its children
+    /// are recomputed the next time they are needed.
+    pub fn transform_mut(&mut self, id: NodeId) -> &mut Transform {
+        self.mark_dirty(id);
+        &mut self.node_mut(id).transform
+    }
+
+    /// Replaces the local transform of a node.
+    pub fn set_transform(&mut self, id: NodeId, transform: Transform) {
+        *self.transform_mut(id) = transform;
+    }
+
+    /// Returns the matrix transforming the node's local space into world space.
+    pub fn world_matrix(&self, id: NodeId) -> Mat4 {
+        let node = self.node(id);
+        if node.world_dirty.get() {
+            let local = node.transform.matrix();
+            let world = match node.parent {
+                Some(parent) => self.world_matrix(parent) * local,
+                None => local,
+            };
+            node.world_matrix.set(world);
+            node.world_dirty.set(false);
+        }
+
+        node.world_matrix.get()
+    }
+
+    /// Returns the position of a node in world space.
+    pub fn world_position(&self, id: NodeId) -> Vec3 {
+        self.world_matrix(id).w_axis.truncate()
+    }
+
+    /// Returns the mesh drawn by a node.
+    pub fn mesh(&self, id: NodeId) -> Option<&Rc<Mesh>> {
+        self.node(id).mesh.as_ref()
+    }
+
+    /// Sets or clears the mesh drawn by a node.
+    pub fn set_mesh(&mut self, id: NodeId, mesh: Option<Rc<Mesh>>) {
+        self.node_mut(id).mesh = mesh;
+    }
+
+    /// Returns the material a node is drawn with.
+    pub fn material(&self, id: NodeId) -> Option<&Rc<Material>> {
+        self.node(id).material.as_ref()
+    }
+
+    /// Sets or clears the material a node is drawn with.
+    pub fn set_material(&mut self, id: NodeId, material: Option<Rc<Material>>) {
+        self.node_mut(id).material = material;
+    }
+
+    /// Returns whether a node is visible. A node is only drawn if it and all of its parents are visible.
+    pub fn is_visible(&self, id: NodeId) -> bool {
+        self.node(id).visible
+    }
+
+    /// Shows or hides a node together with its children.
+    pub fn set_visible(&mut self, id: NodeId, visible: bool) {
+        self.node_mut(id).visible = visible;
+    }
+
+    /// Returns the visible nodes that have both a mesh and a material, parents before their children.
+    pub(crate) fn drawables(&self) -> Vec<(NodeId, &Rc<Mesh>, &Rc<Material>)> {
+        let mut drawables = Vec::new();
+        let mut stack: Vec<NodeId> = self.roots.iter().rev().copied().collect();
+
+        while let Some(id) = stack.pop() {
+            let node = self.node(id);
+            if !node.visible {
+                continue;
+            }
+            if let (Some(mesh), Some(material)) = (&node.mesh, &node.material) {
+                drawables.push((id, mesh, material));
+            }
+            stack.extend(node.children.iter().rev().copied());
+        }
+
+        drawables
+    }
+
+    fn mark_dirty(&self, id: NodeId) {
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            let node = self.node(id);
+            // A node is only cleaned after its parent, so children of an already dirty node are dirty as well
+            if node.world_dirty.replace(true) {
+                continue;
+            }
+            stack.extend(node.children.iter().copied());
+        }
+    }
+
+    fn node(&self, id: NodeId) -> &SceneNode {
+        self.slots
+            .get(id.index)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.node.as_ref())
+            .expect("[FerrousGl Error] Node id does not belong to this scene or was removed.")
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> &mut SceneNode {
+        self.slots
+            .get_mut(id.index)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.node.as_mut())
+            .expect("[FerrousGl Error] Node id does not belong to this scene or was removed.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: f32) -> Transform {
+        Transform::from_translation(Vec3::new(x, 0.0, 0.0))
+    }
+
+    fn x(scene: &Scene, id: NodeId) -> f32 {
+        scene.world_position(id).x
+    }
+
+    #[test]
+    fn world_matrix_combines_parents() {
+        let mut scene = Scene::new();
+        let root = scene.add_node("root", at(1.0).with_scale(Vec3::splat(2.0)), None);
+        let child = scene.add_node("child", at(3.0), Some(root));
+        let grandchild = scene.add_node("grandchild", at(0.5), Some(child));
+
+        assert_eq!(x(&scene, child), 7.0);
+        assert_eq!(x(&scene, grandchild), 8.0);
+    }
+
+    #[test]
+    fn transform_changes_reach_cached_descendants() {
+        let mut scene = Scene::new();
+        let root = scene.add_node("root", at(1.0), None);
+        let child = scene.add_node("child", at(1.0), Some(root));
+        let grandchild = scene.add_node("grandchild", at(1.0), Some(child));
+        assert_eq!(x(&scene, grandchild), 3.0);
+
+        scene.transform_mut(root).translation.x = 10.0;
+        assert_eq!(x(&scene, grandchild), 12.0);
+        assert_eq!(x(&scene, child), 11.0);
+
+        scene.set_transform(child, at(5.0));
+        assert_eq!(x(&scene, grandchild), 16.0);
+        assert_eq!(x(&scene, root), 10.0);
+    }
+
+    #[test]
+    fn transform_changes_reach_descendants_after_only_the_parent_was_cached() {
+        let mut scene = Scene::new();
+        let root = scene.add_node("root", at(1.0), None);
+        let child = scene.add_node("child", at(1.0), Some(root));
+        let grandchild = scene.add_node("grandchild", at(1.0), Some(child));
+
+        // Only the root is clean, its descendants are still dirty from being added
+        assert_eq!(x(&scene, root), 1.0);
+        scene.set_transform(root, at(2.0));
+        assert_eq!(x(&scene, grandchild), 4.0);
+
+        // Now the child is clean while the grandchild is dirty again
+        scene.set_transform(root, at(3.0));
+        assert_eq!(x(&scene, child), 4.0);
+        scene.set_transform(root, at(4.0));
+        assert_eq!(x(&scene, grandchild), 6.0);
+    }
+
+    #[test]
+    fn set_parent_moves_node_and_rejects_cycles() {
+        let mut scene = Scene::new();
+        let a = scene.add_node("a", at(1.0), None);
+        let b = scene.add_node("b", at(10.0), None);
+        let child = scene.add_node("child", at(1.0), Some(a));
+        assert_eq!(x(&scene, child), 2.0);
+
+        scene.set_parent(child, Some(b)).unwrap();
+        assert_eq!(x(&scene, child), 11.0);
+        assert_eq!(scene.children(a), []);
+        assert_eq!(scene.children(b), [child]);
+
+        assert!(scene.set_parent(b, Some(child)).is_err());
+        assert!(scene.set_parent(b, Some(b)).is_err());
+        assert_eq!(scene.parent(b), None);
+
+        scene.set_parent(child, None).unwrap();
+        assert_eq!(x(&scene, child), 1.0);
+        assert_eq!(scene.roots(), [a, b, child]);
+    }
+
+    #[test]
+    fn removed_nodes_are_invalidated() {
+        let mut scene = Scene::new();
+        let root = scene.add_node("root", Transform::IDENTITY, None);
+        let child = scene.add_node("child", Transform::IDENTITY, Some(root));
+        scene.remove_node(root);
+
+        assert!(!scene.contains(root));
+        assert!(!scene.contains(child));
+        assert_eq!(scene.nodes().count(), 0);
+
+        // A reused slot gets a new generation, so old handles stay invalid
+        let new = scene.add_node("new", Transform::IDENTITY, None);
+        assert!(scene.contains(new));
+        assert!(!scene.contains(root) && !scene.contains(child));
+        assert_eq!(scene.find("new"), Some(new));
+        assert_eq!(scene.find("child"), None);
+    }
+}
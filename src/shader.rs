@@ -1,6 +1,8 @@
 extern crate gl;
 
 use gl::types::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::ffi::CString;
 use std::ptr;
 
@@ -11,6 +13,7 @@ use std::path::Path;
 /// Represents a shader to be used for telling the GPU how to "fill in" a meshes vertices.
 pub struct Shader {
     pub id: GLuint,
+    uniform_locations: RefCell<HashMap<String, GLint>>, // Cached uniform locations, -1 for unknown names
 }
 
 impl Shader {
@@ -26,7 +29,10 @@ impl Shader {
             gl::DeleteShader(fragment_shader);
         }
 
-        Shader { id: shader_program }
+        Shader {
+            id: shader_program,
+            uniform_locations: RefCell::new(HashMap::new()),
+        }
     }
 
     /// Creates a new shader using a vertex shader file path and fragment file path.
@@ -54,6 +60,7 @@ impl Shader {
             Ok(new_shader) => {
                 unsafe { gl::DeleteProgram(self.id) };
                 self.id = new_shader.id;
+                self.uniform_locations.borrow_mut().clear();
                 std::mem::forget(new_shader);
                 Ok(())
             },
@@ -147,10 +154,8 @@ impl Shader {
 
     /// Sets a single integer uniform with a name and value. The name of the value should be the same in code and in the shader code.
     pub fn set_uniform_1i(&self, name: &str, value: i32) {
-        let cname = CString::new(name).unwrap();
-        unsafe {
-            let location = gl::GetUniformLocation(self.id, cname.as_ptr());
-            if location != -1 {
+        if let Some(location) = self.uniform_location(name) {
+            unsafe {
                 gl::Uniform1i(location, value);
             }
         }
@@ -158,10 +163,8 @@ impl Shader {
 
     /// Set a single float uniform with a name and value. The name of the value should be the same in code and in the shader code.
     pub fn set_uniform_1f(&self, name: &str, value: f32) {
-        let cname = CString::new(name).unwrap();
-        unsafe {
-            let location = gl::GetUniformLocation(self.id, cname.as_ptr());
-            if location != -1 {
+        if let Some(location) = self.uniform_location(name) {
+            unsafe {
                 gl::Uniform1f(location, value);
             }
         }
@@ -169,10 +172,8 @@ impl Shader {
 
     /// Set a vector of 2 float uniforms with a name and value. The name of the value should be the same in code and in the shader code.
     pub fn set_uniform_2f(&self, name: &str, value_0: f32, value_1: f32) {
-        let cname = CString::new(name).unwrap();
-        unsafe {
-            let location = gl::GetUniformLocation(self.id, cname.as_ptr());
-            if location != -1 {
+        if let Some(location) = self.uniform_location(name) {
+            unsafe {
                 gl::Uniform2f(location, value_0, value_1);
             }
         }
@@ -180,10 +181,8 @@ impl Shader {
 
     /// Set a vector of 3 float uniforms with a name and value. The name of the value should be the same in code and in the shader code.
     pub fn set_uniform_3f(&self, name: &str, v0: f32, v1: f32, v2: f32) {
-        let cname = CString::new(name).unwrap();
-        unsafe {
-            let location = gl::GetUniformLocation(self.id, cname.as_ptr());
-            if location != -1 {
+        if let Some(location) = self.uniform_location(name) {
+            unsafe {
                 gl::Uniform3f(location, v0, v1, v2);
             }
         }
@@ -191,10 +190,8 @@ impl Shader {
 
     /// Set a vector of 4 float uniforms with a name and value. The name of the value should be the same in code and in the shader code.
     pub fn set_uniform_4f(&self, name: &str, v0: f32, v1: f32, v2: f32, v3: f32) {
-        let cname = CString::new(name).unwrap();
-        unsafe {
-            let location = gl::GetUniformLocation(self.id, cname.as_ptr());
-            if location != -1 {
+        if let Some(location) = self.uniform_location(name) {
+            unsafe {
                 gl::Uniform4f(location, v0, v1, v2, v3);
             }
         }
@@ -202,10 +199,8 @@ impl Shader {
 
     // Sets a 4x4 matrix uniform with a name and value. The name of the value should be the same in code and in the shader code.
     pub fn set_uniform_matrix_4fv(&self, name: &str, matrix: &[f32]) {
-        let cname = CString::new(name).unwrap();
-        unsafe {
-            let location = gl::GetUniformLocation(self.id, cname.as_ptr());
-            if location != -1 {
+        if let Some(location) = self.uniform_location(name) {
+            unsafe {
                 gl::UniformMatrix4fv(location, 1, gl::FALSE, matrix.as_ptr());
             }
         }
@@ -213,14 +208,34 @@ impl Shader {
 
     /// Sets a texture sampler uniform with a name and value. The name of the value should be the same in code and in the shader code.
     pub fn set_uniform_texture(&self, name: &str, texture_unit: u32) {
-        let cname = CString::new(name).unwrap();
-        unsafe {
-            let location = gl::GetUniformLocation(self.id, cname.as_ptr());
-            if location != -1 {
+        if let Some(location) = self.uniform_location(name) {
+            unsafe {
                 gl::Uniform1i(location, texture_unit as i32);
             }
         }
     }
+
+    /// Returns the location of a uniform, or `None` if the shader has no active uniform with that name.
+    /// Locations are looked up once and cached until the shader is recompiled.
+    pub(crate) fn uniform_location(&self, name: &str) -> Option<GLint> {
+        if let Some(location) = self.uniform_locations.borrow().get(name) {
+            return (*location != -1).then_some(*location);
+        }
+
+        let cname = CString::new(name).unwrap();
+        let location = unsafe { gl::GetUniformLocation(self.id, cname.as_ptr()) };
+        self.uniform_locations.borrow_mut().insert(name.to_string(), location);
+        (location != -1).then_some(location)
+    }
+
+    /// Creates a shader without a program, for tests that never call into OpenGL.
+    #[cfg(test)]
+    pub(crate) fn without_program() -> Self {
+        Shader {
+            id: 0,
+            uniform_locations: RefCell::new(HashMap::new()),
+        }
+    }
 }
 
 fn create_whitespace_cstring_with_len(len: usize) -> CString {
@@ -0,0 +1,707 @@
+extern crate gl;
+extern crate glam;
+
+use gl::types::*;
+use glam::{Mat4, Vec3};
+
+use crate::framebuffer::preserve_framebuffer_bindings;
+use crate::lighting::{DirectionalLight, PointLight, SpotLight};
+use crate::texture::TextureFormat;
+use crate::{Camera, CubemapTexture, GlWindow, Projection, Scene, Shader, TextureArray};
+
+/// The maximum number of cascades of a [`CascadedShadowMap`].
+pub const MAX_SHADOW_CASCADES: usize = 4;
+
+const DEPTH_VERTEX_SHADER: &str = r#"
+#version 330 core
+layout (location = 0) in vec3 aPos;
+
+uniform mat4 model;
+uniform mat4 lightSpaceMatrix;
+
+out vec3 worldPosition;
+
+void main() {
+    vec4 world = model * vec4(aPos, 1.0);
+    worldPosition = world.xyz;
+    gl_Position = lightSpaceMatrix * world;
+}
+"#;
+
+const DEPTH_FRAGMENT_SHADER: &str = r#"
+#version 330 core
+
+void main() {
+}
+"#;
+
+const POINT_DEPTH_FRAGMENT_SHADER: &str = r#"
+#version 330 core
+in vec3 worldPosition;
+
+uniform vec3 lightPosition;
+uniform float farPlane;
+
+void main() {
+    // Store the linear distance to the light, so the shadow test doesn't depend on the cube face
+    gl_FragDepth = length(worldPosition - lightPosition) / farPlane;
+}
+"#;
+
+/// GLSL functions for sampling the shadow maps of this module with PCF or PCSS filtering. Insert them into a fragment
+/// shader with [`with_shadow_functions`]. All functions return 1 for fully lit and 0 for fully shadowed fragments.
+///
+/// - `shadowCoords(lightSpaceMatrix, worldPosition)` projects a world position into shadow map coordinates.
+/// - `shadowPcf(shadowMap, layer, coords, bias, kernelRadius)` averages a square of depth comparisons.
+/// - `shadowPcss(shadowMap, layer, coords, bias, lightSize)` softens the shadow with the distance to the occluder,
+///   `lightSize` is the largest penumbra in texels.
+/// - `cascadedShadow(shadowMap, matrices, splits, cascadeCount, worldPosition, viewDepth, bias, kernelRadius)` picks the
+///   cascade for a fragment from its view space depth and filters it with PCF.
+/// - `pointShadow(shadowMap, lightPosition, farPlane, worldPosition, bias)` samples a point light cubemap with PCF.
+pub const SHADOW_FUNCTIONS_GLSL: &str = r#"
+vec3 shadowCoords(mat4 lightSpaceMatrix, vec3 worldPosition) {
+    vec4 clip = lightSpaceMatrix * vec4(worldPosition, 1.0);
+    return clip.xyz / clip.w * 0.5 + 0.5;
+}
+
+float shadowPcf(sampler2DArray shadowMap, float layer, vec3 coords, float bias, int kernelRadius) {
+    if (coords.z > 1.0) {
+        return 1.0;
+    }
+
+    vec2 texelSize = 1.0 / vec2(textureSize(shadowMap, 0).xy);
+    float lit = 0.0;
+    for (int x = -kernelRadius; x <= kernelRadius; x++) {
+        for (int y = -kernelRadius; y <= kernelRadius; y++) {
+            float depth = texture(shadowMap, vec3(coords.xy + vec2(x, y) * texelSize, layer)).r;
+            lit += coords.z - bias > depth ? 0.0 : 1.0;
+        }
+    }
+
+    float kernelSize = float(kernelRadius * 2 + 1);
+    return lit / (kernelSize * kernelSize);
+}
+
+float shadowPcss(sampler2DArray shadowMap, float layer, vec3 coords, float bias, float lightSize) {
+    if (coords.z > 1.0) {
+        return 1.0;
+    }
+
+    vec2 texelSize = 1.0 / vec2(textureSize(shadowMap, 0).xy);
+
+    // Find the average depth of the occluders around the fragment
+    float blockerDepth = 0.0;
+    float blockers = 0.0;
+    for (int x = -2; x <= 2; x++) {
+        for (int y = -2; y <= 2; y++) {
+            float depth = texture(shadowMap, vec3(coords.xy + vec2(x, y) * texelSize * lightSize * 0.5, layer)).r;
+            if (depth < coords.z - bias) {
+                blockerDepth += depth;
+                blockers += 1.0;
+            }
+        }
+    }
+
+    if (blockers == 0.0) {
+        return 1.0;
+    }
+
+    // The penumbra grows with the distance between occluder and receiver
+    blockerDepth /= blockers;
+    float penumbra = clamp((coords.z - blockerDepth) / blockerDepth * lightSize, 1.0, lightSize);
+
+    float lit = 0.0;
+    for (int x = -3; x <= 3; x++) {
+        for (int y = -3; y <= 3; y++) {
+            vec2 offset = vec2(x, y) / 3.0 * penumbra * texelSize;
+            float depth = texture(shadowMap, vec3(coords.xy + offset, layer)).r;
+            lit += coords.z - bias > depth ? 0.0 : 1.0;
+        }
+    }
+
+    return lit / 49.0;
+}
+
+float cascadedShadow(
+    sampler2DArray shadowMap,
+    mat4 matrices[4],
+    float splits[4],
+    int cascadeCount,
+    vec3 worldPosition,
+    float viewDepth,
+    float bias,
+    int kernelRadius
+) {
+    int cascade = cascadeCount - 1;
+    for (int i = 0; i < cascadeCount; i++) {
+        if (viewDepth < splits[i]) {
+            cascade = i;
+            break;
+        }
+    }
+
+    if (viewDepth > splits[cascadeCount - 1]) {
+        return 1.0;
+    }
+
+    // Texels of farther cascades cover more of the world, so they need a larger bias
+    vec3 coords = shadowCoords(matrices[cascade], worldPosition);
+    return shadowPcf(shadowMap, float(cascade), coords, bias * float(cascade + 1), kernelRadius);
+}
+
+float pointShadow(samplerCube shadowMap, vec3 lightPosition, float farPlane, vec3 worldPosition, float bias) {
+    const vec3 offsets[20] = vec3[](
+        vec3(1, 1, 1), vec3(1, -1, 1), vec3(-1, -1, 1), vec3(-1, 1, 1),
+        vec3(1, 1, -1), vec3(1, -1, -1), vec3(-1, -1, -1), vec3(-1, 1, -1),
+        vec3(1, 1, 0), vec3(1, -1, 0), vec3(-1, -1, 0), vec3(-1, 1, 0),
+        vec3(1, 0, 1), vec3(-1, 0, 1), vec3(1, 0, -1), vec3(-1, 0, -1),
+        vec3(0, 1, 1), vec3(0, -1, 1), vec3(0, -1, -1), vec3(0, 1, -1)
+    );
+
+    vec3 toFragment = worldPosition - lightPosition;
+    float currentDepth = length(toFragment);
+    if (currentDepth > farPlane) {
+        return 1.0;
+    }
+
+    float diskRadius = 0.002 * currentDepth + 0.01;
+    float lit = 0.0;
+    for (int i = 0; i < 20; i++) {
+        float depth = texture(shadowMap, toFragment + offsets[i] * diskRadius).r * farPlane;
+        lit += currentDepth - bias > depth ? 0.0 : 1.0;
+    }
+
+    return lit / 20.0;
+}
+"#;
+
+/// Inserts [`SHADOW_FUNCTIONS_GLSL`] into a fragment shader source, right after its `#version` line.
+pub fn with_shadow_functions(fragment_source: &str) -> String {
+    let source = fragment_source.trim_start();
+    match source.strip_prefix("#version") {
+        Some(rest) => {
+            let line_end = rest.find('\n').map_or(rest.len(), |i| i + 1);
+            format!("#version{}{}{}", &rest[..line_end], SHADOW_FUNCTIONS_GLSL, &rest[line_end..])
+        }
+        None => format!("{}{}", SHADOW_FUNCTIONS_GLSL, source),
+    }
+}
+
+/// Settings of a [`CascadedShadowMap`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CascadedShadowConfig {
+    /// The width and height of every cascade in pixels.
+    pub resolution: u32,
+    /// The number of cascades, from 1 to [`MAX_SHADOW_CASCADES`].
+    pub cascades: u32,
+    /// Blends between evenly spaced cascade splits (0) and logarithmic ones (1), which give more detail close to the camera.
+    pub split_lambda: f32,
+    /// Shadows are only drawn up to this distance from the camera, or the camera's far plane if that is closer.
+    pub max_distance: f32,
+    /// How far in front of each cascade occluders are still rendered, so objects outside of the view still cast shadows into it.
+    pub caster_distance: f32,
+}
+
+impl Default for CascadedShadowConfig {
+    fn default() -> Self {
+        CascadedShadowConfig {
+            resolution: 2048,
+            cascades: 4,
+            split_lambda: 0.75,
+            max_distance: 100.0,
+            caster_distance: 50.0,
+        }
+    }
+}
+
+/// Cascaded shadow maps for a directional light. The camera's view is split into depth ranges that each get their own
+/// shadow map fitted around them, so nearby shadows stay sharp while far away shadows still cover the whole view.
+pub struct CascadedShadowMap {
+    depth: TextureArray,
+    pass: DepthPass,
+    config: CascadedShadowConfig,
+    matrices: Vec<Mat4>,
+    splits: Vec<f32>,
+}
+
+impl CascadedShadowMap {
+    /// Creates the shadow map with its depth textures.
+    pub fn new(config: CascadedShadowConfig) -> Result<Self, String> {
+        if config.cascades == 0 || config.cascades as usize > MAX_SHADOW_CASCADES {
+            return Err(format!(
+                "[FerrousGl Error] A cascaded shadow map needs 1 to {} cascades, got {}.",
+                MAX_SHADOW_CASCADES, config.cascades
+            ));
+        }
+
+        let depth = TextureArray::new_empty(config.resolution, config.resolution, config.cascades, TextureFormat::Depth32F)?;
+        set_border_sampling(gl::TEXTURE_2D_ARRAY, depth.id);
+
+        Ok(CascadedShadowMap {
+            depth,
+            pass: DepthPass::new(DEPTH_FRAGMENT_SHADER),
+            config,
+            matrices: vec![Mat4::IDENTITY; config.cascades as usize],
+            splits: vec![0.0; config.cascades as usize],
+        })
+    }
+
+    /// Fits the cascades around the camera's view for a light. Call it whenever the camera or light moved, before rendering.
+    pub fn update(&mut self, light: &DirectionalLight, camera: &Camera, aspect_ratio: f32) {
+        let cascades = self.config.cascades as usize;
+        let near = camera.near.max(0.001);
+        let far = camera.far.min(self.config.max_distance);
+        let direction = light.direction.try_normalize().unwrap_or(Vec3::NEG_Y);
+        let up = if direction.abs().dot(Vec3::Y) > 0.99 { Vec3::Z } else { Vec3::Y };
+
+        let mut previous_split = near;
+        for i in 0..cascades {
+            let fraction = (i + 1) as f32 / cascades as f32;
+            let split = split_distance(near, far, fraction, self.config.split_lambda);
+
+            let corners = frustum_corners(camera, aspect_ratio, previous_split, split);
+            let center = corners.iter().sum::<Vec3>() / corners.len() as f32;
+            // A bounding sphere keeps the cascade size constant while the camera rotates, which avoids shimmering
+            let radius = corners.iter().map(|corner| corner.distance(center)).fold(0.0, f32::max).ceil();
+
+            // Snap the center to whole shadow map texels, so shadow edges don't crawl while the camera moves
+            let texel_size = radius * 2.0 / self.config.resolution as f32;
+            let light_rotation = Mat4::look_at_rh(Vec3::ZERO, direction, up);
+            let mut light_center = light_rotation.transform_point3(center);
+            light_center.x = (light_center.x / texel_size).floor() * texel_size;
+            light_center.y = (light_center.y / texel_size).floor() * texel_size;
+            let center = light_rotation.inverse().transform_point3(light_center);
+
+            let distance = radius + self.config.caster_distance;
+            let view = Mat4::look_at_rh(center - direction * distance, center, up);
+            let projection = Mat4::orthographic_rh_gl(-radius, radius, -radius, radius, 0.0, distance + radius);
+
+            self.matrices[i] = projection * view;
+            self.splits[i] = split;
+            previous_split = split;
+        }
+    }
+
+    /// Renders the depth of every cascade. For each cascade the built-in depth shader is bound with its
+    /// `lightSpaceMatrix`, and `draw` has to set the `model` matrix and render the shadow casting meshes.
+    pub fn render<F: FnMut(&Shader)>(&self, mut draw: F) -> Result<(), String> {
+        for (layer, matrix) in self.matrices.iter().enumerate() {
+            self.pass.render(&self.pass.shader, *matrix, self.config.resolution, attach_layer(&self.depth, layer), &mut draw)?;
+        }
+        Ok(())
+    }
+
+    /// Renders the depth of every cascade with a custom shader, for example one that discards transparent fragments.
+    /// The shader receives the `lightSpaceMatrix` uniform.
+    pub fn render_with_shader<F: FnMut(&Shader)>(&self, shader: &Shader, mut draw: F) -> Result<(), String> {
+        for (layer, matrix) in self.matrices.iter().enumerate() {
+            self.pass.render(shader, *matrix, self.config.resolution, attach_layer(&self.depth, layer), &mut draw)?;
+        }
+        Ok(())
+    }
+
+    /// Renders every visible mesh of a scene into the cascades.
+    pub fn render_scene(&self, window: &GlWindow, scene: &Scene) -> Result<(), String> {
+        self.render(|shader| draw_scene(window, scene, shader))
+    }
+
+    /// Binds the shadow map to a texture unit and uploads the uniforms used by `cascadedShadow`: the sampler `name`,
+    /// `{name}Matrices`, `{name}Splits` and `{name}CascadeCount`. Also binds the shader program.
+    pub fn apply(&self, shader: &Shader, name: &str, texture_unit: u32) {
+        shader.bind_program();
+        self.depth.bind(texture_unit);
+        shader.set_uniform_texture(name, texture_unit);
+        shader.set_uniform_1i(&format!("{}CascadeCount", name), self.config.cascades as i32);
+
+        for (i, (matrix, split)) in self.matrices.iter().zip(&self.splits).enumerate() {
+            shader.set_uniform_matrix_4fv(&format!("{}Matrices[{}]", name, i), &matrix.to_cols_array());
+            shader.set_uniform_1f(&format!("{}Splits[{}]", name, i), *split);
+        }
+    }
+
+    /// Returns the depth textures, one layer per cascade.
+    pub fn texture(&self) -> &TextureArray {
+        &self.depth
+    }
+
+    /// Returns the light space matrix of every cascade.
+    pub fn light_space_matrices(&self) -> &[Mat4] {
+        &self.matrices
+    }
+
+    /// Returns the view space distance at which each cascade ends.
+    pub fn cascade_splits(&self) -> &[f32] {
+        &self.splits
+    }
+
+    /// Returns the settings of the shadow map.
+    pub fn config(&self) -> &CascadedShadowConfig {
+        &self.config
+    }
+}
+
+/// A perspective shadow map for a spot light. It is stored as a single layer texture array,
+/// so it is sampled with the same `shadowPcf` and `shadowPcss` functions as cascades.
+pub struct SpotShadowMap {
+    depth: TextureArray,
+    pass: DepthPass,
+    resolution: u32,
+    matrix: Mat4,
+}
+
+impl SpotShadowMap {
+    /// Creates the shadow map with a square depth texture of the given size.
+    pub fn new(resolution: u32) -> Result<Self, String> {
+        let depth = TextureArray::new_empty(resolution, resolution, 1, TextureFormat::Depth32F)?;
+        set_border_sampling(gl::TEXTURE_2D_ARRAY, depth.id);
+
+        Ok(SpotShadowMap {
+            depth,
+            pass: DepthPass::new(DEPTH_FRAGMENT_SHADER),
+            resolution,
+            matrix: Mat4::IDENTITY,
+        })
+    }
+
+    /// Points the shadow map along the light's cone. Call it whenever the light moved, before rendering.
+    pub fn update(&mut self, light: &SpotLight) {
+        let direction = light.direction.try_normalize().unwrap_or(Vec3::NEG_Y);
+        let up = if direction.abs().dot(Vec3::Y) > 0.99 { Vec3::Z } else { Vec3::Y };
+        let near = (light.range * 0.001).max(0.01);
+
+        let view = Mat4::look_at_rh(light.position, light.position + direction, up);
+        let fov = (light.outer_angle * 2.0).clamp(0.01, std::f32::consts::PI - 0.01);
+        let projection = Mat4::perspective_rh_gl(fov, 1.0, near, light.range);
+        self.matrix = projection * view;
+    }
+
+    /// Renders the depth of the shadow casters. The built-in depth shader is bound with the `lightSpaceMatrix`,
+    /// and `draw` has to set the `model` matrix and render the shadow casting meshes.
+    pub fn render<F: FnMut(&Shader)>(&self, mut draw: F) -> Result<(), String> {
+        self.pass.render(&self.pass.shader, self.matrix, self.resolution, attach_layer(&self.depth, 0), &mut draw)
+    }
+
+    /// Renders the depth of the shadow casters with a custom shader, which receives the `lightSpaceMatrix` uniform.
+    pub fn render_with_shader<F: FnMut(&Shader)>(&self, shader: &Shader, mut draw: F) -> Result<(), String> {
+        self.pass.render(shader, self.matrix, self.resolution, attach_layer(&self.depth, 0), &mut draw)
+    }
+
+    /// Renders every visible mesh of a scene into the shadow map.
+    pub fn render_scene(&self, window: &GlWindow, scene: &Scene) -> Result<(), String> {
+        self.render(|shader| draw_scene(window, scene, shader))
+    }
+
+    /// Binds the shadow map to a texture unit and uploads the sampler `name` and `{name}Matrix`.
+    /// Also binds the shader program.
+    pub fn apply(&self, shader: &Shader, name: &str, texture_unit: u32) {
+        shader.bind_program();
+        self.depth.bind(texture_unit);
+        shader.set_uniform_texture(name, texture_unit);
+        shader.set_uniform_matrix_4fv(&format!("{}Matrix", name), &self.matrix.to_cols_array());
+    }
+
+    /// Returns the depth texture.
+    pub fn texture(&self) -> &TextureArray {
+        &self.depth
+    }
+
+    /// Returns the light space matrix.
+    pub fn light_space_matrix(&self) -> Mat4 {
+        self.matrix
+    }
+}
+
+/// An omnidirectional shadow map for a point light, rendered into the six faces of a depth cubemap.
+/// The faces store the linear distance to the light divided by the light's range.
+pub struct PointShadowMap {
+    depth: CubemapTexture,
+    pass: DepthPass,
+    resolution: u32,
+    matrices: [Mat4; 6],
+    position: Vec3,
+    far: f32,
+}
+
+impl PointShadowMap {
+    /// Creates the shadow map with cubemap faces of the given size.
+    pub fn new(resolution: u32) -> Result<Self, String> {
+        let depth = CubemapTexture::new_empty(resolution, TextureFormat::Depth32F)?;
+
+        Ok(PointShadowMap {
+            depth,
+            pass: DepthPass::new(POINT_DEPTH_FRAGMENT_SHADER),
+            resolution,
+            matrices: [Mat4::IDENTITY; 6],
+            position: Vec3::ZERO,
+            far: 1.0,
+        })
+    }
+
+    /// Places the shadow map at the light. Call it whenever the light moved, before rendering.
+    pub fn update(&mut self, light: &PointLight) {
+        let near = (light.range * 0.001).max(0.01);
+        let projection = Mat4::perspective_rh_gl(std::f32::consts::FRAC_PI_2, 1.0, near, light.range);
+
+        for (matrix, view) in self.matrices.iter_mut().zip(CubemapTexture::face_views(light.position)) {
+            *matrix = projection * view;
+        }
+        self.position = light.position;
+        self.far = light.range;
+    }
+
+    /// Renders the depth of the shadow casters into every face. The built-in depth shader is bound with the face's
+    /// `lightSpaceMatrix`, and `draw` has to set the `model` matrix and render the shadow casting meshes.
+    pub fn render<F: FnMut(&Shader)>(&self, draw: F) -> Result<(), String> {
+        self.render_with_shader(&self.pass.shader, draw)
+    }
+
+    /// Renders the depth of the shadow casters with a custom shader. Besides the `lightSpaceMatrix`, it receives
+    /// `lightPosition` and `farPlane` and has to write the linear distance to the light divided by `farPlane` to `gl_FragDepth`.
+    pub fn render_with_shader<F: FnMut(&Shader)>(&self, shader: &Shader, mut draw: F) -> Result<(), String> {
+        shader.bind_program();
+        shader.set_uniform_3f("lightPosition", self.position.x, self.position.y, self.position.z);
+        shader.set_uniform_1f("farPlane", self.far);
+
+        for (face, matrix) in self.matrices.iter().enumerate() {
+            let attach = || unsafe {
+                gl::FramebufferTexture2D(
+                    gl::FRAMEBUFFER,
+                    gl::DEPTH_ATTACHMENT,
+                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + face as GLenum,
+                    self.depth.id,
+                    0,
+                );
+            };
+            self.pass.render(shader, *matrix, self.resolution, attach, &mut draw)?;
+        }
+        Ok(())
+    }
+
+    /// Renders every visible mesh of a scene into the shadow map.
+    pub fn render_scene(&self, window: &GlWindow, scene: &Scene) -> Result<(), String> {
+        self.render(|shader| draw_scene(window, scene, shader))
+    }
+
+    /// Binds the shadow map to a texture unit and uploads the uniforms used by `pointShadow`: the sampler `name`,
+    /// `{name}Position` and `{name}Far`. Also binds the shader program.
+    pub fn apply(&self, shader: &Shader, name: &str, texture_unit: u32) {
+        shader.bind_program();
+        self.depth.bind(texture_unit);
+        shader.set_uniform_texture(name, texture_unit);
+        shader.set_uniform_3f(&format!("{}Position", name), self.position.x, self.position.y, self.position.z);
+        shader.set_uniform_1f(&format!("{}Far", name), self.far);
+    }
+
+    /// Returns the depth cubemap.
+    pub fn texture(&self) -> &CubemapTexture {
+        &self.depth
+    }
+
+    /// Returns the light space matrix of every face, ordered +X, -X, +Y, -Y, +Z, -Z.
+    pub fn light_space_matrices(&self) -> &[Mat4; 6] {
+        &self.matrices
+    }
+}
+
+/// A depth-only framebuffer and shader, shared by the shadow map kinds.
+struct DepthPass {
+    framebuffer_id: GLuint,
+    shader: Shader,
+}
+
+impl DepthPass {
+    fn new(fragment_source: &str) -> Self {
+        let mut framebuffer_id = 0;
+        unsafe {
+            gl::GenFramebuffers(1, &mut framebuffer_id);
+        }
+
+        DepthPass {
+            framebuffer_id,
+            shader: Shader::new_from_source(DEPTH_VERTEX_SHADER, fragment_source),
+        }
+    }
+
+    /// Attaches a depth texture with `attach`, clears it and calls `draw` with the shader bound,
+    /// restoring the previous framebuffers, viewport and depth state afterwards.
+    fn render<A: FnOnce(), F: FnMut(&Shader)>(
+        &self,
+        shader: &Shader,
+        matrix: Mat4,
+        resolution: u32,
+        attach: A,
+        draw: &mut F,
+    ) -> Result<(), String> {
+        preserve_framebuffer_bindings(|| unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer_id);
+            attach();
+            gl::DrawBuffer(gl::NONE);
+            gl::ReadBuffer(gl::NONE);
+
+            if gl::CheckFramebufferStatus(gl::FRAMEBUFFER) != gl::FRAMEBUFFER_COMPLETE {
+                return Err("[FerrousGl Error] Shadow map framebuffer is not complete!".to_string());
+            }
+
+            let mut previous_viewport = [0; 4];
+            gl::GetIntegerv(gl::VIEWPORT, previous_viewport.as_mut_ptr());
+
+            // Shadow maps always use standard depth, even while the window renders with reverse-Z
+            let mut previous_depth_func = 0;
+            let mut previous_depth_mask = 0;
+            let mut previous_clear_depth = 0.0;
+            let mut previous_clip_depth = gl::NEGATIVE_ONE_TO_ONE as GLint;
+            let depth_test = gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE;
+            gl::GetIntegerv(gl::DEPTH_FUNC, &mut previous_depth_func);
+            gl::GetBooleanv(gl::DEPTH_WRITEMASK, &mut previous_depth_mask);
+            gl::GetDoublev(gl::DEPTH_CLEAR_VALUE, &mut previous_clear_depth);
+            let clip_control = gl::ClipControl::is_loaded();
+            if clip_control {
+                gl::GetIntegerv(gl::CLIP_DEPTH_MODE, &mut previous_clip_depth);
+                gl::ClipControl(gl::LOWER_LEFT, gl::NEGATIVE_ONE_TO_ONE);
+            }
+            gl::Enable(gl::DEPTH_TEST);
+            gl::DepthMask(gl::TRUE);
+            gl::DepthFunc(gl::LESS);
+            gl::ClearDepth(1.0);
+
+            gl::Viewport(0, 0, resolution as i32, resolution as i32);
+            gl::Clear(gl::DEPTH_BUFFER_BIT);
+
+            shader.bind_program();
+            shader.set_uniform_matrix_4fv("lightSpaceMatrix", &matrix.to_cols_array());
+            draw(shader);
+            shader.unbind_program();
+
+            if clip_control {
+                gl::ClipControl(gl::LOWER_LEFT, previous_clip_depth as GLenum);
+            }
+            if !depth_test {
+                gl::Disable(gl::DEPTH_TEST);
+            }
+            gl::DepthMask(previous_depth_mask);
+            gl::DepthFunc(previous_depth_func as GLenum);
+            gl::ClearDepth(previous_clear_depth);
+
+            let [x, y, width, height] = previous_viewport;
+            gl::Viewport(x, y, width, height);
+            Ok(())
+        })
+    }
+}
+
+impl Drop for DepthPass {
+    /// Cleans up the framebuffer.
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.framebuffer_id);
+        }
+    }
+}
+
+fn attach_layer(depth: &TextureArray, layer: usize) -> impl FnOnce() + '_ {
+    move || unsafe {
+        gl::FramebufferTextureLayer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, depth.id, 0, layer as i32);
+    }
+}
+
+fn draw_scene(window: &GlWindow, scene: &Scene, shader: &Shader) {
+    for (id, mesh, _) in scene.drawables() {
+        shader.set_uniform_matrix_4fv("model", &scene.world_matrix(id).to_cols_array());
+        window.render_mesh(mesh);
+    }
+}
+
+/// Makes samples outside of a shadow map read as the far plane, so everything outside of it is lit.
+fn set_border_sampling(target: GLenum, texture_id: GLuint) {
+    unsafe {
+        gl::BindTexture(target, texture_id);
+        gl::TexParameteri(target, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_BORDER as i32);
+        gl::TexParameteri(target, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_BORDER as i32);
+        gl::TexParameterfv(target, gl::TEXTURE_BORDER_COLOR, [1.0f32; 4].as_ptr());
+        gl::BindTexture(target, 0);
+    }
+}
+
+/// Returns the distance a fraction of the way from near to far, blending between an even split and a
+/// logarithmic one with `lambda`.
+fn split_distance(near: f32, far: f32, fraction: f32, lambda: f32) -> f32 {
+    let uniform_split = near + (far - near) * fraction;
+    let log_split = near * (far / near).powf(fraction);
+    lambda * log_split + (1.0 - lambda) * uniform_split
+}
+
+/// Returns the world space corners of the part of the camera's view between two distances.
+fn frustum_corners(camera: &Camera, aspect_ratio: f32, near: f32, far: f32) -> [Vec3; 8] {
+    let (forward, right, up) = (camera.forward(), camera.right(), camera.up());
+    let half_size = |distance: f32| match camera.projection {
+        Projection::Perspective { fov_y } => {
+            let half_height = distance * (fov_y * 0.5).tan();
+            (half_height * aspect_ratio, half_height)
+        }
+        Projection::Orthographic { height } => (height * 0.5 * aspect_ratio, height * 0.5),
+    };
+
+    let mut corners = [Vec3::ZERO; 8];
+    for (i, distance) in [near, far].into_iter().enumerate() {
+        let (half_width, half_height) = half_size(distance);
+        let center = camera.position + forward * distance;
+        corners[i * 4] = center - right * half_width - up * half_height;
+        corners[i * 4 + 1] = center + right * half_width - up * half_height;
+        corners[i * 4 + 2] = center + right * half_width + up * half_height;
+        corners[i * 4 + 3] = center - right * half_width + up * half_height;
+    }
+
+    corners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shadow_functions_go_after_the_version_line() {
+        let source = with_shadow_functions("\n  #version 330 core\nvoid main() {}\n");
+        assert!(source.starts_with("#version 330 core\n"));
+        assert!(source.ends_with("void main() {}\n"));
+        assert_eq!(source.find(SHADOW_FUNCTIONS_GLSL), Some("#version 330 core\n".len()));
+
+        let source = with_shadow_functions("void main() {}");
+        assert_eq!(source, format!("{}void main() {{}}", SHADOW_FUNCTIONS_GLSL));
+
+        // A version line without a trailing newline keeps the functions after it
+        assert_eq!(with_shadow_functions("#version 330 core"), format!("#version 330 core{}", SHADOW_FUNCTIONS_GLSL));
+    }
+
+    #[test]
+    fn cascade_splits_blend_even_and_logarithmic() {
+        let (near, far) = (1.0, 100.0);
+        assert_eq!(split_distance(near, far, 0.5, 0.0), 50.5);
+        assert!((split_distance(near, far, 0.5, 1.0) - 10.0).abs() < 1e-4);
+        assert!((split_distance(near, far, 0.5, 0.5) - 30.25).abs() < 1e-4);
+
+        for lambda in [0.0, 0.5, 1.0] {
+            assert!((split_distance(near, far, 0.0, lambda) - near).abs() < 1e-4);
+            assert!((split_distance(near, far, 1.0, lambda) - far).abs() < 1e-3);
+
+            let splits: Vec<f32> = (1..=4).map(|i| split_distance(near, far, i as f32 / 4.0, lambda)).collect();
+            assert!(splits.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", splits);
+        }
+    }
+
+    #[test]
+    fn frustum_corners_span_the_view_between_distances() {
+        let camera = Camera::new_perspective(90.0, 0.1, 100.0);
+        let corners = frustum_corners(&camera, 2.0, 1.0, 10.0);
+
+        // The camera looks down -Z, a 90 degree field of view is as high as it is far
+        assert!(corners[0].abs_diff_eq(Vec3::new(-2.0, -1.0, -1.0), 1e-5));
+        assert!(corners[2].abs_diff_eq(Vec3::new(2.0, 1.0, -1.0), 1e-5));
+        assert!(corners[4].abs_diff_eq(Vec3::new(-20.0, -10.0, -10.0), 1e-4));
+        assert!(corners[6].abs_diff_eq(Vec3::new(20.0, 10.0, -10.0), 1e-4));
+
+        let camera = Camera::new_orthographic(4.0, 0.1, 100.0);
+        let corners = frustum_corners(&camera, 1.0, 1.0, 10.0);
+        assert!(corners[1].abs_diff_eq(Vec3::new(2.0, -2.0, -1.0), 1e-5));
+        assert!(corners[7].abs_diff_eq(Vec3::new(-2.0, 2.0, -10.0), 1e-5));
+    }
+}
@@ -0,0 +1,99 @@
+extern crate gl;
+
+use glam::{Mat3, Mat4};
+
+use crate::cubemap::cube_mesh;
+use crate::{CubemapTexture, GlWindow, Mesh, Shader};
+
+const SKYBOX_VERTEX_SHADER: &str = r#"
+#version 330 core
+layout (location = 0) in vec3 aPos;
+
+uniform mat4 projection;
+uniform mat4 view;
+uniform float farDepth;
+
+out vec3 direction;
+
+void main() {
+    direction = aPos;
+    vec4 position = projection * view * vec4(aPos, 1.0);
+    // Puts the skybox at the far plane, behind everything else: z = w normally, z = 0 with reverse-Z
+    gl_Position = vec4(position.xy, position.w * farDepth, position.w);
+}
+"#;
+
+const SKYBOX_FRAGMENT_SHADER: &str = r#"
+#version 330 core
+in vec3 direction;
+
+uniform samplerCube skybox;
+
+out vec4 FragColor;
+
+void main() {
+    FragColor = texture(skybox, direction);
+}
+"#;
+
+/// A helper to draw a cubemap as the sky behind a scene. Render it after the rest of the scene, so only the
+/// pixels not covered by other geometry have to be shaded.
+pub struct Skybox {
+    mesh: Mesh,
+    shader: Shader,
+}
+
+impl Skybox {
+    /// Creates a new skybox. The cube mesh and shader are created once and can be reused with any cubemap.
+    pub fn new() -> Self {
+        Skybox {
+            mesh: cube_mesh(),
+            shader: Shader::new_from_source(SKYBOX_VERTEX_SHADER, SKYBOX_FRAGMENT_SHADER),
+        }
+    }
+
+    /// Renders the cubemap around the camera onto the current bound framebuffer. The translation of the view matrix
+    /// is ignored, so the sky never moves closer. Depth testing is set up to draw only where nothing else has been
+    /// drawn yet, matching [`GlWindow::is_reverse_z`], and the previous depth state is restored afterwards.
+    pub fn render(&self, window: &GlWindow, cubemap: &CubemapTexture, view: Mat4, projection: Mat4) {
+        let rotation_only = Mat4::from_mat3(Mat3::from_mat4(view));
+
+        unsafe {
+            let mut previous_depth_func = 0;
+            let mut previous_depth_mask = 0;
+            gl::GetIntegerv(gl::DEPTH_FUNC, &mut previous_depth_func);
+            gl::GetBooleanv(gl::DEPTH_WRITEMASK, &mut previous_depth_mask);
+            let depth_test_enabled = gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE;
+
+            // The sky ends up exactly at the far plane, which passes against the cleared depth
+            let reverse_z = window.is_reverse_z();
+            gl::DepthFunc(if reverse_z { gl::GEQUAL } else { gl::LEQUAL });
+            gl::DepthMask(gl::FALSE);
+            gl::Enable(gl::DEPTH_TEST);
+
+            self.shader.bind_program();
+            self.shader.set_uniform_1f("farDepth", if reverse_z { 0.0 } else { 1.0 });
+            cubemap.bind(0);
+            self.shader.set_uniform_texture("skybox", 0);
+            self.shader.set_uniform_matrix_4fv("view", rotation_only.to_cols_array().as_ref());
+            self.shader.set_uniform_matrix_4fv("projection", projection.to_cols_array().as_ref());
+
+            window.render_mesh(&self.mesh);
+
+            cubemap.unbind();
+            self.shader.unbind_program();
+
+            gl::DepthFunc(previous_depth_func as u32);
+            gl::DepthMask(previous_depth_mask);
+            if !depth_test_enabled {
+                gl::Disable(gl::DEPTH_TEST);
+            }
+        }
+    }
+}
+
+impl Default for Skybox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
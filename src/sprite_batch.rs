@@ -0,0 +1,482 @@
+extern crate gl;
+extern crate glam;
+
+use gl::types::*;
+use glam::{Mat4, Vec2, Vec4};
+use std::mem;
+use std::ptr;
+use std::rc::Rc;
+
+use crate::texture::TextureFormat;
+use crate::texture_atlas::AtlasRegion;
+use crate::{GlWindow, Shader, Texture};
+
+const SPRITE_VERTEX_SHADER: &str = r#"
+#version 330 core
+layout (location = 0) in vec2 aPos;
+layout (location = 1) in vec2 aTexCoord;
+layout (location = 2) in vec4 aColor;
+
+uniform mat4 projection;
+
+out vec2 texCoord;
+out vec4 color;
+
+void main() {
+    texCoord = aTexCoord;
+    color = aColor;
+    gl_Position = projection * vec4(aPos, 0.0, 1.0);
+}
+"#;
+
+const SPRITE_FRAGMENT_SHADER: &str = r#"
+#version 330 core
+in vec2 texCoord;
+in vec4 color;
+
+uniform sampler2D spriteTexture;
+
+out vec4 FragColor;
+
+void main() {
+    FragColor = texture(spriteTexture, texCoord) * color;
+}
+"#;
+
+// Position, texture coordinate and color
+const FLOATS_PER_VERTEX: usize = 8;
+
+/// A textured or colored quad drawn by a [`SpriteBatch`]. Positions and sizes are in pixels when the batch uses
+/// the window's pixel projection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprite {
+    pub position: Vec2,
+    pub size: Vec2,
+    /// Rotation around the origin in radians, clockwise on screen.
+    pub rotation: f32,
+    pub scale: Vec2,
+    /// The point the sprite is placed at and rotated around, from (0, 0) at the top left to (1, 1) at the bottom right.
+    pub origin: Vec2,
+    /// The part of the texture shown, from the top left to the bottom right texture coordinate.
+    pub uv_min: Vec2,
+    pub uv_max: Vec2,
+    /// Multiplied with the texture color.
+    pub tint: Vec4,
+    /// With [`SpriteSortMode::Layer`], sprites on higher layers are drawn on top of lower ones. The layer only
+    /// decides the draw order, it never reaches the vertex data or the depth buffer.
+    pub layer: f32,
+}
+
+impl Sprite {
+    /// Creates a sprite showing the whole texture at a position with a size.
+    pub fn new(position: Vec2, size: Vec2) -> Self {
+        Sprite {
+            position,
+            size,
+            rotation: 0.0,
+            scale: Vec2::ONE,
+            origin: Vec2::ZERO,
+            uv_min: Vec2::ZERO,
+            uv_max: Vec2::ONE,
+            tint: Vec4::ONE,
+            layer: 0.0,
+        }
+    }
+
+    /// Creates a sprite showing a region of a texture atlas at its original size.
+    pub fn from_region(position: Vec2, region: &AtlasRegion) -> Self {
+        Sprite::new(position, Vec2::new(region.width as f32, region.height as f32)).with_uv(region.uv_min, region.uv_max)
+    }
+
+    /// Returns the sprite with its rotation replaced.
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Returns the sprite with its scale replaced.
+    pub fn with_scale(mut self, scale: Vec2) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Returns the sprite with its origin replaced.
+    pub fn with_origin(mut self, origin: Vec2) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Returns the sprite showing a different part of the texture.
+    pub fn with_uv(mut self, uv_min: Vec2, uv_max: Vec2) -> Self {
+        self.uv_min = uv_min;
+        self.uv_max = uv_max;
+        self
+    }
+
+    /// Returns the sprite with its tint replaced.
+    pub fn with_tint(mut self, tint: Vec4) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// Returns the sprite with its layer replaced.
+    pub fn with_layer(mut self, layer: f32) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    fn write_vertices(&self, vertices: &mut Vec<f32>) {
+        let (sin, cos) = self.rotation.sin_cos();
+        let size = self.size * self.scale;
+        let offset = self.origin * size;
+
+        let corners = [
+            (Vec2::new(0.0, 0.0), Vec2::new(self.uv_min.x, self.uv_min.y)),
+            (Vec2::new(size.x, 0.0), Vec2::new(self.uv_max.x, self.uv_min.y)),
+            (Vec2::new(size.x, size.y), Vec2::new(self.uv_max.x, self.uv_max.y)),
+            (Vec2::new(0.0, size.y), Vec2::new(self.uv_min.x, self.uv_max.y)),
+        ];
+
+        for (corner, uv) in corners {
+            let local = corner - offset;
+            let x = self.position.x + local.x * cos - local.y * sin;
+            let y = self.position.y + local.x * sin + local.y * cos;
+            vertices.extend_from_slice(&[x, y, uv.x, uv.y, self.tint.x, self.tint.y, self.tint.z, self.tint.w]);
+        }
+    }
+}
+
+/// The order sprites of a [`SpriteBatch`] are drawn in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpriteSortMode {
+    /// Sorted by layer, keeping the order sprites were added in within a layer, so overlapping sprites are always
+    /// drawn correctly. Consecutive sprites with the same texture share a draw call, so add the sprites of a layer
+    /// grouped by texture (or use an atlas) for the fewest draw calls.
+    Layer,
+    /// Sorted by texture only, which needs the fewest draw calls but ignores layers. For sprites that don't overlap.
+    Texture,
+    /// Drawn in the order they were added, starting a new draw call whenever the texture changes.
+    Submission,
+}
+
+/// Collects sprites and draws them with as few draw calls as possible, by sorting them and drawing all consecutive
+/// sprites with the same texture from one streaming vertex buffer.
+///
+/// Sprites are queued between [`SpriteBatch::begin`] and [`SpriteBatch::end`]. The batch doesn't change the blend mode
+/// or depth test, so enable `BlendMode::Alpha` and disable depth testing for transparent sprites.
+pub struct SpriteBatch {
+    vertex_array: GLuint,
+    vertex_buffer: GLuint,
+    index_buffer: GLuint,
+    capacity: usize,
+    shader: Shader,
+    white_texture: Rc<Texture>,
+    projection: Mat4,
+    sort_mode: SpriteSortMode,
+    sprites: Vec<(Rc<Texture>, Sprite)>,
+    vertices: Vec<f32>,
+    draw_calls: usize,
+}
+
+impl SpriteBatch {
+    /// Creates a sprite batch that draws up to `capacity` sprites per draw call.
+    pub fn new(capacity: usize) -> Result<Self, String> {
+        if capacity == 0 || capacity * 4 > u32::MAX as usize {
+            return Err(format!("[FerrousGl Error] Invalid sprite batch capacity {}.", capacity));
+        }
+
+        let white_texture = Rc::new(Texture::from_raw(1, 1, TextureFormat::Rgba8, &[255; 4])?);
+
+        let indices: Vec<u32> = (0..capacity as u32)
+            .flat_map(|sprite| {
+                let first = sprite * 4;
+                [first, first + 1, first + 2, first, first + 2, first + 3]
+            })
+            .collect();
+
+        let mut vertex_array = 0;
+        let mut vertex_buffer = 0;
+        let mut index_buffer = 0;
+
+        unsafe {
+            gl::GenVertexArrays(1, &mut vertex_array);
+            gl::BindVertexArray(vertex_array);
+
+            gl::GenBuffers(1, &mut vertex_buffer);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vertex_buffer);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                (capacity * 4 * FLOATS_PER_VERTEX * mem::size_of::<f32>()) as GLsizeiptr,
+                ptr::null(),
+                gl::STREAM_DRAW,
+            );
+
+            gl::GenBuffers(1, &mut index_buffer);
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, index_buffer);
+            gl::BufferData(
+                gl::ELEMENT_ARRAY_BUFFER,
+                mem::size_of_val(indices.as_slice()) as GLsizeiptr,
+                indices.as_ptr() as *const GLvoid,
+                gl::STATIC_DRAW,
+            );
+
+            let stride = (FLOATS_PER_VERTEX * mem::size_of::<f32>()) as GLsizei;
+            for (index, size, offset) in [(0, 2, 0), (1, 2, 2), (2, 4, 4)] {
+                gl::EnableVertexAttribArray(index);
+                gl::VertexAttribPointer(
+                    index,
+                    size,
+                    gl::FLOAT,
+                    gl::FALSE,
+                    stride,
+                    (offset * mem::size_of::<f32>()) as *const GLvoid,
+                );
+            }
+
+            gl::BindVertexArray(0);
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
+
+        Ok(SpriteBatch {
+            vertex_array,
+            vertex_buffer,
+            index_buffer,
+            capacity,
+            shader: Shader::new_from_source(SPRITE_VERTEX_SHADER, SPRITE_FRAGMENT_SHADER),
+            white_texture,
+            projection: Mat4::IDENTITY,
+            sort_mode: SpriteSortMode::Layer,
+            sprites: Vec::new(),
+            vertices: Vec::new(),
+            draw_calls: 0,
+        })
+    }
+
+    /// Returns an orthographic projection mapping pixels to the screen, with (0, 0) at the top left corner.
+    pub fn pixel_projection(width: u32, height: u32) -> Mat4 {
+        Mat4::orthographic_rh_gl(0.0, width as f32, height as f32, 0.0, -1.0, 1.0)
+    }
+
+    /// Sets the order sprites are drawn in, see [`SpriteSortMode`].
+    pub fn set_sort_mode(&mut self, sort_mode: SpriteSortMode) {
+        self.sort_mode = sort_mode;
+    }
+
+    /// Starts collecting sprites, positioned in pixels of the window's framebuffer.
+    pub fn begin(&mut self, window: &GlWindow) {
+        let (width, height) = window.get_framebuffer_size();
+        self.begin_with_projection(Self::pixel_projection(width.max(1) as u32, height.max(1) as u32));
+    }
+
+    /// Starts collecting sprites with a custom projection, for example one that includes a 2D camera.
+    pub fn begin_with_projection(&mut self, projection: Mat4) {
+        self.projection = projection;
+        self.sprites.clear();
+    }
+
+    /// Queues a textured sprite.
+    pub fn draw(&mut self, texture: &Rc<Texture>, sprite: Sprite) {
+        self.sprites.push((Rc::clone(texture), sprite));
+    }
+
+    /// Queues a rectangle filled with the sprite's tint.
+    pub fn draw_rect(&mut self, sprite: Sprite) {
+        self.sprites.push((Rc::clone(&self.white_texture), sprite));
+    }
+
+    /// Sorts and draws all queued sprites onto the current bound framebuffer. Returns the number of draw calls.
+    pub fn end(&mut self) -> usize {
+        sort_sprites(&mut self.sprites, self.sort_mode, |texture| texture.id);
+
+        self.draw_calls = 0;
+        if self.sprites.is_empty() {
+            return 0;
+        }
+
+        self.shader.bind_program();
+        self.shader.set_uniform_matrix_4fv("projection", &self.projection.to_cols_array());
+        self.shader.set_uniform_texture("spriteTexture", 0);
+
+        let mut polygon_mode = [gl::FILL as GLint; 2];
+        unsafe {
+            gl::GetIntegerv(gl::POLYGON_MODE, polygon_mode.as_mut_ptr());
+            gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL);
+            gl::BindVertexArray(self.vertex_array);
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vertex_buffer);
+        }
+
+        let sprites = mem::take(&mut self.sprites);
+        let mut start = 0;
+        while start < sprites.len() {
+            let texture = &sprites[start].0;
+            let run = run_length(&sprites[start..], self.capacity, |texture| texture.id);
+
+            self.vertices.clear();
+            for (_, sprite) in &sprites[start..start + run] {
+                sprite.write_vertices(&mut self.vertices);
+            }
+
+            texture.bind(0);
+            unsafe {
+                // Orphan the buffer so the driver doesn't wait for the previous draw call to finish with it
+                gl::BufferData(
+                    gl::ARRAY_BUFFER,
+                    (self.capacity * 4 * FLOATS_PER_VERTEX * mem::size_of::<f32>()) as GLsizeiptr,
+                    ptr::null(),
+                    gl::STREAM_DRAW,
+                );
+                gl::BufferSubData(
+                    gl::ARRAY_BUFFER,
+                    0,
+                    mem::size_of_val(self.vertices.as_slice()) as GLsizeiptr,
+                    self.vertices.as_ptr() as *const GLvoid,
+                );
+                gl::DrawElements(gl::TRIANGLES, (run * 6) as i32, gl::UNSIGNED_INT, ptr::null());
+            }
+
+            self.draw_calls += 1;
+            start += run;
+        }
+
+        unsafe {
+            gl::BindVertexArray(0);
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::PolygonMode(gl::FRONT_AND_BACK, polygon_mode[0] as GLenum);
+        }
+        self.shader.unbind_program();
+
+        // Keep the allocation for the next frame
+        self.sprites = sprites;
+        self.sprites.clear();
+        self.draw_calls
+    }
+
+    /// Returns the number of draw calls the last [`SpriteBatch::end`] needed.
+    pub fn draw_calls(&self) -> usize {
+        self.draw_calls
+    }
+
+    /// Returns the number of queued sprites.
+    pub fn len(&self) -> usize {
+        self.sprites.len()
+    }
+
+    /// Returns true if no sprites are queued.
+    pub fn is_empty(&self) -> bool {
+        self.sprites.is_empty()
+    }
+}
+
+/// Orders queued sprites for drawing. Both sorts are stable, so equal sprites keep the order they were added in.
+fn sort_sprites<T>(sprites: &mut [(T, Sprite)], sort_mode: SpriteSortMode, texture_id: impl Fn(&T) -> GLuint) {
+    match sort_mode {
+        SpriteSortMode::Layer => sprites.sort_by(|(_, a), (_, b)| a.layer.total_cmp(&b.layer)),
+        SpriteSortMode::Texture => sprites.sort_by_key(|(texture, _)| texture_id(texture)),
+        SpriteSortMode::Submission => {}
+    }
+}
+
+/// Returns how many of the first sprites share the first sprite's texture, up to the buffer capacity.
+/// They are drawn with one draw call.
+fn run_length<T>(sprites: &[(T, Sprite)], capacity: usize, texture_id: impl Fn(&T) -> GLuint) -> usize {
+    let Some((first, _)) = sprites.first() else {
+        return 0;
+    };
+
+    let id = texture_id(first);
+    sprites
+        .iter()
+        .take(capacity)
+        .take_while(|(texture, _)| texture_id(texture) == id)
+        .count()
+}
+
+impl Drop for SpriteBatch {
+    /// Cleans up the buffers.
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.vertex_array);
+            gl::DeleteBuffers(1, &self.vertex_buffer);
+            gl::DeleteBuffers(1, &self.index_buffer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertex_positions(sprite: &Sprite) -> Vec<Vec2> {
+        let mut vertices = Vec::new();
+        sprite.write_vertices(&mut vertices);
+        vertices
+            .chunks_exact(FLOATS_PER_VERTEX)
+            .map(|vertex| Vec2::new(vertex[0], vertex[1]))
+            .collect()
+    }
+
+    fn queue(sprites: &[(u32, f32)]) -> Vec<(u32, Sprite)> {
+        sprites
+            .iter()
+            .enumerate()
+            .map(|(i, &(texture, layer))| (texture, Sprite::new(Vec2::new(i as f32, 0.0), Vec2::ONE).with_layer(layer)))
+            .collect()
+    }
+
+    fn order(sprites: &[(u32, Sprite)]) -> Vec<u32> {
+        sprites.iter().map(|(_, sprite)| sprite.position.x as u32).collect()
+    }
+
+    #[test]
+    fn write_vertices_places_corners_around_the_origin() {
+        let sprite = Sprite::new(Vec2::new(10.0, 20.0), Vec2::new(4.0, 2.0))
+            .with_origin(Vec2::splat(0.5))
+            .with_uv(Vec2::new(0.25, 0.5), Vec2::new(0.75, 1.0))
+            .with_tint(Vec4::new(1.0, 0.5, 0.25, 1.0));
+
+        let mut vertices = Vec::new();
+        sprite.write_vertices(&mut vertices);
+        assert_eq!(vertices.len(), 4 * FLOATS_PER_VERTEX);
+        assert_eq!(&vertices[..FLOATS_PER_VERTEX], &[8.0, 19.0, 0.25, 0.5, 1.0, 0.5, 0.25, 1.0]);
+        assert_eq!(&vertices[2 * FLOATS_PER_VERTEX..2 * FLOATS_PER_VERTEX + 4], &[12.0, 21.0, 0.75, 1.0]);
+    }
+
+    #[test]
+    fn write_vertices_applies_scale_and_rotation() {
+        let sprite = Sprite::new(Vec2::ZERO, Vec2::new(2.0, 1.0))
+            .with_scale(Vec2::splat(2.0))
+            .with_rotation(std::f32::consts::FRAC_PI_2);
+
+        let expected = [Vec2::ZERO, Vec2::new(0.0, 4.0), Vec2::new(-2.0, 4.0), Vec2::new(-2.0, 0.0)];
+        for (position, expected) in vertex_positions(&sprite).into_iter().zip(expected) {
+            assert!(position.abs_diff_eq(expected, 1e-5), "{} != {}", position, expected);
+        }
+    }
+
+    #[test]
+    fn layer_sort_keeps_submission_order_within_a_layer() {
+        let mut sprites = queue(&[(2, 1.0), (1, 0.0), (2, 0.0), (1, 1.0), (3, -1.0)]);
+        sort_sprites(&mut sprites, SpriteSortMode::Layer, |&texture| texture);
+        assert_eq!(order(&sprites), [4, 1, 2, 0, 3]);
+    }
+
+    #[test]
+    fn texture_and_submission_sorts() {
+        let mut sprites = queue(&[(2, 1.0), (1, 0.0), (2, 0.0), (1, 1.0)]);
+        sort_sprites(&mut sprites, SpriteSortMode::Texture, |&texture| texture);
+        assert_eq!(order(&sprites), [1, 3, 0, 2]);
+
+        let mut sprites = queue(&[(2, 1.0), (1, 0.0), (2, 0.0)]);
+        sort_sprites(&mut sprites, SpriteSortMode::Submission, |&texture| texture);
+        assert_eq!(order(&sprites), [0, 1, 2]);
+    }
+
+    #[test]
+    fn runs_split_on_texture_changes_and_capacity() {
+        let sprites = queue(&[(1, 0.0), (1, 0.0), (1, 0.0), (2, 0.0), (1, 0.0)]);
+        assert_eq!(run_length(&sprites, 10, |&texture| texture), 3);
+        assert_eq!(run_length(&sprites, 2, |&texture| texture), 2);
+        assert_eq!(run_length(&sprites[3..], 10, |&texture| texture), 1);
+        assert_eq!(run_length::<u32>(&[], 10, |&texture| texture), 0);
+    }
+}
@@ -2,88 +2,235 @@ extern crate gl;
 extern crate image;
 
 use gl::types::*;
+use glam::Vec4;
 use image::DynamicImage;
 use std::path::Path;
 use std::ptr;
 
+use crate::compressed_texture::CompressedImage;
+use crate::framebuffer::Rect;
+use crate::mipmap::{generate_mip_chain, mip_level_count};
+use crate::readback::{check_rect, fits_inside, read_framebuffer, PixelComponent, PixelFormat, PixelReadback};
+
 /// Represents an OpenGL texture.
 pub struct Texture {
     pub(crate) id: GLuint,
     pub(crate) width: u32,
     pub(crate) height: u32,
+    pub(crate) format: TextureFormat,
 }
 
 impl Texture {
     /// Creates a new texture from an image file.
+    /// DDS and KTX2 files are uploaded in their block compressed format, see [`Texture::from_compressed`].
     pub fn new_from_file(path: &Path) -> Result<Self, String> {
+        if CompressedImage::is_container(path) {
+            return Self::from_compressed(&CompressedImage::open(path)?);
+        }
+
         let img = image::open(&Path::new(path)).map_err(|e| e.to_string())?;
         Self::from_image(&img)
     }
 
+    /// Creates a new texture from an image file, converting the image into the given texture format.
+    /// For example, an OpenEXR file can be loaded into `TextureFormat::Rgba16F` to save memory.
+    pub fn new_from_file_with_format(path: &Path, format: TextureFormat) -> Result<Self, String> {
+        let img = image::open(path).map_err(|e| e.to_string())?;
+        Self::from_image_with_format(&img, format)
+    }
+
+    /// Creates a new texture from precompressed (BCn) data, uploading the whole mip chain as is.
+    /// Returns an error if the driver does not support the compressed format.
+    pub fn from_compressed(img: &CompressedImage) -> Result<Self, String> {
+        img.format.check_support()?;
+
+        let mut texture_id = 0;
+
+        unsafe {
+            gl::GenTextures(1, &mut texture_id);
+            gl::BindTexture(gl::TEXTURE_2D, texture_id);
+
+            for (level, data) in img.levels.iter().enumerate() {
+                gl::CompressedTexImage2D(
+                    gl::TEXTURE_2D,
+                    level as i32,
+                    img.format.internal_format(),
+                    img.width.checked_shr(level as u32).unwrap_or(0).max(1) as i32,
+                    img.height.checked_shr(level as u32).unwrap_or(0).max(1) as i32,
+                    0,
+                    data.len() as i32,
+                    data.as_ptr() as *const GLvoid,
+                );
+            }
+
+            let min_filter = if img.levels.len() > 1 {
+                gl::LINEAR_MIPMAP_LINEAR
+            } else {
+                gl::LINEAR
+            };
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, min_filter as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            // Compressed textures can't generate their own mipmaps, so only the uploaded levels may be used
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, img.levels.len() as i32 - 1);
+
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+
+        Ok(Texture {
+            id: texture_id,
+            width: img.width,
+            height: img.height,
+            format: img.format,
+        })
+    }
+
     /// Creates a new texture from an in-memory image.
+    /// The texture format is picked from the image, so 16-bit and float images keep their precision.
     pub fn from_image(img: &DynamicImage) -> Result<Self, String> {
-        let img = img.to_rgba8();
-        let (width, height) = img.dimensions();
+        Self::from_image_with_format(img, TextureFormat::for_image(img))
+    }
 
+    /// Creates a new texture from an in-memory image, converting the image into the given texture format.
+    pub fn from_image_with_format(img: &DynamicImage, format: TextureFormat) -> Result<Self, String> {
+        Self::from_image_with_options(
+            img,
+            TextureOptions {
+                format: Some(format),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Creates a new texture from an image file, using the given format and mipmap options.
+    pub fn new_from_file_with_options(path: &Path, options: TextureOptions) -> Result<Self, String> {
+        let img = image::open(path).map_err(|e| e.to_string())?;
+        Self::from_image_with_options(&img, options)
+    }
+
+    /// Creates a new texture from an in-memory image, using the given format and mipmap options.
+    /// Integer textures never get mipmaps.
+    pub fn from_image_with_options(img: &DynamicImage, options: TextureOptions) -> Result<Self, String> {
+        Ok(Self::from_prepared(&PreparedImage::new(img, options)?, None))
+    }
+
+    /// Uploads pixel data that has been converted on the CPU, optionally staging each level through a pixel
+    /// unpack buffer so the driver can copy it to the GPU asynchronously.
+    pub(crate) fn from_prepared(prepared: &PreparedImage, pixel_buffer: Option<GLuint>) -> Self {
+        let format = prepared.format;
         let mut texture_id = 0;
 
         unsafe {
             gl::GenTextures(1, &mut texture_id);
             gl::BindTexture(gl::TEXTURE_2D, texture_id);
 
+            let filter = format.default_filter();
             gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
             gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, filter as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, filter as i32);
 
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::RGBA as i32,
-                width as i32,
-                height as i32,
-                0,
-                gl::RGBA,
-                gl::UNSIGNED_BYTE,
-                img.as_ptr() as *const GLvoid,
-            );
+            for (level, data) in prepared.levels.iter().enumerate() {
+                let width = prepared.width.checked_shr(level as u32).unwrap_or(0).max(1);
+                let height = prepared.height.checked_shr(level as u32).unwrap_or(0).max(1);
 
-            gl::GenerateMipmap(gl::TEXTURE_2D);
+                match pixel_buffer {
+                    Some(buffer) => {
+                        gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, buffer);
+                        // Orphan the previous storage so the driver doesn't have to wait for the last upload
+                        gl::BufferData(gl::PIXEL_UNPACK_BUFFER, data.len() as isize, ptr::null(), gl::STREAM_DRAW);
+                        let mapped = gl::MapBufferRange(
+                            gl::PIXEL_UNPACK_BUFFER,
+                            0,
+                            data.len() as isize,
+                            gl::MAP_WRITE_BIT | gl::MAP_INVALIDATE_BUFFER_BIT,
+                        );
+                        // Unmapping fails if the driver lost the buffer contents while mapped
+                        let staged = !mapped.is_null() && {
+                            ptr::copy_nonoverlapping(data.as_ptr(), mapped as *mut u8, data.len());
+                            gl::UnmapBuffer(gl::PIXEL_UNPACK_BUFFER) == gl::TRUE
+                        };
+
+                        if staged {
+                            // With a pixel unpack buffer bound, the data pointer is an offset into the buffer
+                            upload_2d(gl::TEXTURE_2D, level as i32, format, width, height, ptr::null());
+                            gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0);
+                        } else {
+                            // Retry the level straight from memory
+                            gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0);
+                            upload_2d(gl::TEXTURE_2D, level as i32, format, width, height, data.as_ptr() as *const GLvoid);
+                        }
+                    }
+                    None => {
+                        upload_2d(gl::TEXTURE_2D, level as i32, format, width, height, data.as_ptr() as *const GLvoid);
+                    }
+                }
+            }
+
+            if prepared.generate_mipmaps {
+                gl::GenerateMipmap(gl::TEXTURE_2D);
+            }
 
             gl::BindTexture(gl::TEXTURE_2D, 0);
         }
 
-        Ok(Texture {
+        Texture {
             id: texture_id,
-            width,
-            height,
-        })
+            width: prepared.width,
+            height: prepared.height,
+            format,
+        }
     }
 
-    /// Creates an empty texture with the specified width and height.
+    /// Creates a new texture from a hand-authored mip chain, starting with the full size image.
+    /// Each following image has to be half the size of the previous one (rounded down, at least 1).
+    /// Only the given levels are used and trilinear filtering is enabled.
+    pub fn from_mip_chain(levels: &[DynamicImage], format: Option<TextureFormat>) -> Result<Self, String> {
+        let first = levels
+            .first()
+            .ok_or("[FerrousGl Error] A mip chain needs at least one image!")?;
+
+        let texture = Self::from_image_with_options(
+            first,
+            TextureOptions {
+                format,
+                mipmaps: MipmapGeneration::None,
+            },
+        )?;
+
+        for (level, img) in levels.iter().enumerate().skip(1) {
+            texture.upload_mip_level(level as u32, img)?;
+        }
+
+        texture.set_mip_levels(0, levels.len() as u32 - 1);
+        if levels.len() > 1 {
+            texture.set_mipmap_and_filtering(MipmapType::Linear, FilterMode::Linear);
+        }
+
+        Ok(texture)
+    }
+
+    /// Creates an empty RGBA8 texture with the specified width and height.
     pub fn new_empty(width: u32, height: u32) -> Result<Self, String> {
+        Self::new_empty_with_format(width, height, TextureFormat::Rgba8)
+    }
+
+    /// Creates an empty texture with the specified width, height and texture format.
+    pub fn new_empty_with_format(width: u32, height: u32, format: TextureFormat) -> Result<Self, String> {
         let mut texture_id = 0;
 
         unsafe {
             gl::GenTextures(1, &mut texture_id);
             gl::BindTexture(gl::TEXTURE_2D, texture_id);
 
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::RGBA as i32,
-                width as i32,
-                height as i32,
-                0,
-                gl::RGBA,
-                gl::UNSIGNED_BYTE,
-                ptr::null(),
-            );
+            upload_2d(gl::TEXTURE_2D, 0, format, width, height, ptr::null());
 
+            let filter = format.default_filter();
             gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
             gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, filter as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, filter as i32);
 
             gl::BindTexture(gl::TEXTURE_2D, 0);
         }
@@ -92,9 +239,168 @@ impl Texture {
             id: texture_id,
             width,
             height,
+            format,
         })
     }
 
+    /// Creates a new texture from raw pixel data. The data has to be tightly packed rows, starting at the bottom row,
+    /// in the layout described by the format's `pixel_format` and `pixel_type` (see [`TextureFormat::bytes_per_pixel`]).
+    pub fn from_raw(width: u32, height: u32, format: TextureFormat, data: &[u8]) -> Result<Self, String> {
+        let texture = Self::new_empty_with_format(width, height, format)?;
+        texture.update_region(0, 0, width, height, data)?;
+        Ok(texture)
+    }
+
+    /// Replaces a rectangular region of the texture with new pixel data, without reallocating the texture.
+    /// The data uses the same layout as in [`Texture::from_raw`]. Mipmaps are not updated automatically.
+    pub fn update_region(&self, x: u32, y: u32, width: u32, height: u32, data: &[u8]) -> Result<(), String> {
+        if self.format.is_compressed() {
+            return Err(format!(
+                "[FerrousGl Error] Can not update a region of a compressed {:?} texture.",
+                self.format
+            ));
+        }
+
+        if !fits_inside([x, y], [width, height], [self.width, self.height]) {
+            return Err(format!(
+                "[FerrousGl Error] Region {}x{} at ({}, {}) is outside of the {}x{} texture.",
+                width, height, x, y, self.width, self.height
+            ));
+        }
+
+        let expected_len = width as usize * height as usize * self.format.bytes_per_pixel();
+        if data.len() != expected_len {
+            return Err(format!(
+                "[FerrousGl Error] Expected {} bytes of {:?} pixel data, but got {}.",
+                expected_len,
+                self.format,
+                data.len()
+            ));
+        }
+
+        self.with_bound(|| unsafe {
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexSubImage2D(
+                gl::TEXTURE_2D,
+                0,
+                x as i32,
+                y as i32,
+                width as i32,
+                height as i32,
+                self.format.pixel_format(),
+                self.format.pixel_type(),
+                data.as_ptr() as *const GLvoid,
+            );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+        });
+
+        Ok(())
+    }
+
+    /// Resizes the texture, keeping its format and parameters. The previous contents are discarded.
+    /// If the texture had mip levels they are reallocated at the new size, so the texture stays complete.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.with_bound(|| unsafe {
+            let mut mip_width = 0;
+            gl::GetTexLevelParameteriv(gl::TEXTURE_2D, 1, gl::TEXTURE_WIDTH, &mut mip_width);
+
+            upload_2d(gl::TEXTURE_2D, 0, self.format, width, height, ptr::null());
+
+            // Old mip levels keep their old size, which would leave the texture incomplete
+            if mip_width > 0 {
+                if self.format.is_integer() || self.format.is_compressed() {
+                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, 0);
+                } else {
+                    gl::GenerateMipmap(gl::TEXTURE_2D);
+                }
+            }
+        });
+
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Generates all mip levels from the first level, allocating them if needed.
+    /// Useful after rendering into a [`crate::RenderTexture`] or updating the texture.
+    /// Does nothing for integer and compressed textures.
+    pub fn generate_mipmaps(&self) {
+        if self.format.is_integer() || self.format.is_compressed() {
+            return;
+        }
+
+        self.with_bound(|| unsafe {
+            gl::GenerateMipmap(gl::TEXTURE_2D);
+        });
+    }
+
+    /// Limits which mip levels are used for sampling and generated by [`Texture::generate_mipmaps`].
+    pub fn set_mip_levels(&self, base_level: u32, max_level: u32) {
+        self.with_bound(|| unsafe {
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_BASE_LEVEL, base_level as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, max_level as i32);
+        });
+    }
+
+    /// Returns the size of a mip level, which halves with every level down to 1x1.
+    pub fn mip_level_size(&self, level: u32) -> (u32, u32) {
+        (
+            self.width.checked_shr(level).unwrap_or(0).max(1),
+            self.height.checked_shr(level).unwrap_or(0).max(1),
+        )
+    }
+
+    /// Replaces a mip level with a hand-authored image, converted into the texture's format.
+    /// The image has to match the size returned by [`Texture::mip_level_size`].
+    pub fn upload_mip_level(&self, level: u32, img: &DynamicImage) -> Result<(), String> {
+        let (width, height) = self.mip_level_size(level);
+        if img.width() != width || img.height() != height {
+            return Err(format!(
+                "[FerrousGl Error] Mip level {} has to be {}x{}, but the image is {}x{}.",
+                level,
+                width,
+                height,
+                img.width(),
+                img.height()
+            ));
+        }
+
+        self.upload_mip_level_raw(level, &image_to_bytes(img, self.format)?)
+    }
+
+    /// Replaces a mip level with raw pixel data, laid out as in [`Texture::from_raw`].
+    pub fn upload_mip_level_raw(&self, level: u32, data: &[u8]) -> Result<(), String> {
+        if self.format.is_compressed() {
+            return Err(format!(
+                "[FerrousGl Error] Can not upload a mip level of a compressed {:?} texture.",
+                self.format
+            ));
+        }
+
+        if level >= mip_level_count(self.width, self.height) {
+            return Err(format!(
+                "[FerrousGl Error] Mip level {} is out of range for a {}x{} texture.",
+                level, self.width, self.height
+            ));
+        }
+
+        let (width, height) = self.mip_level_size(level);
+        let expected_len = width as usize * height as usize * self.format.bytes_per_pixel();
+        if data.len() != expected_len {
+            return Err(format!(
+                "[FerrousGl Error] Expected {} bytes of {:?} pixel data, but got {}.",
+                expected_len,
+                self.format,
+                data.len()
+            ));
+        }
+
+        self.with_bound(|| unsafe {
+            upload_2d(gl::TEXTURE_2D, level as i32, self.format, width, height, data.as_ptr() as *const GLvoid);
+        });
+
+        Ok(())
+    }
+
     /// Binds the texture to a specific texture unit which can be used to set a uniform texture.
     pub fn bind(&self, texture_unit: u32) {
         unsafe {
@@ -111,57 +417,67 @@ impl Texture {
     }
 
     /// Sets the preferred Texture Mipmap Type or Texture Filtering Mode such as None, Linear and Nearest.
-    /// Do this after binding a texture, otherwise it will not take effect.
+    /// The texture does not need to be bound for this to take effect.
     pub fn set_mipmap_and_filtering(&self, mipmap_type: MipmapType, base_filter: FilterMode) {
-        unsafe {
-            // Set minification filter based on both mipmap type and base filter
-            match (mipmap_type, base_filter) {
-                (MipmapType::None, FilterMode::Linear) => {
-                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-                }
-                (MipmapType::None, FilterMode::Nearest) => {
-                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
-                }
-                (MipmapType::Nearest, FilterMode::Linear) => {
-                    gl::TexParameteri(
-                        gl::TEXTURE_2D,
-                        gl::TEXTURE_MIN_FILTER,
-                        gl::NEAREST_MIPMAP_LINEAR as i32,
-                    );
-                }
-                (MipmapType::Nearest, FilterMode::Nearest) => {
-                    gl::TexParameteri(
-                        gl::TEXTURE_2D,
-                        gl::TEXTURE_MIN_FILTER,
-                        gl::NEAREST_MIPMAP_NEAREST as i32,
-                    );
-                }
-                (MipmapType::Linear, FilterMode::Linear) => {
-                    gl::TexParameteri(
-                        gl::TEXTURE_2D,
-                        gl::TEXTURE_MIN_FILTER,
-                        gl::LINEAR_MIPMAP_LINEAR as i32,
-                    );
-                }
-                (MipmapType::Linear, FilterMode::Nearest) => {
-                    gl::TexParameteri(
-                        gl::TEXTURE_2D,
-                        gl::TEXTURE_MIN_FILTER,
-                        gl::LINEAR_MIPMAP_NEAREST as i32,
-                    );
-                }
-            }
+        set_texture_filtering(gl::TEXTURE_2D, self.id, mipmap_type, base_filter);
+    }
 
-            // Set magnification filter (mipmaps don't affect magnification)
-            match base_filter {
-                FilterMode::Linear => {
-                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
-                }
-                FilterMode::Nearest => {
-                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
-                }
-            }
+    /// Sets how texture coordinates outside of the 0 to 1 range are handled, horizontally (s) and vertically (t).
+    pub fn set_wrap(&self, wrap_s: WrapMode, wrap_t: WrapMode) {
+        self.with_bound(|| unsafe {
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, GLenum::from(wrap_s) as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, GLenum::from(wrap_t) as i32);
+        });
+    }
+
+    /// Sets the color that is sampled outside of the texture when using `WrapMode::ClampToBorder`.
+    pub fn set_border_color(&self, color: Vec4) {
+        self.with_bound(|| unsafe {
+            gl::TexParameterfv(
+                gl::TEXTURE_2D,
+                gl::TEXTURE_BORDER_COLOR,
+                color.to_array().as_ptr(),
+            );
+        });
+    }
+
+    /// Sets the anisotropic filtering level, which keeps textures sharp when viewed at steep angles.
+    /// The level is clamped to what the driver supports (see [`max_anisotropy`]) and 1.0 disables it.
+    /// Does nothing if the driver does not support anisotropic filtering.
+    pub fn set_anisotropy(&self, level: f32) {
+        let max_level = max_anisotropy();
+        if max_level <= 1.0 {
+            return;
         }
+
+        self.with_bound(|| unsafe {
+            gl::TexParameterf(
+                gl::TEXTURE_2D,
+                TEXTURE_MAX_ANISOTROPY,
+                level.clamp(1.0, max_level),
+            );
+        });
+    }
+
+    /// Sets the level of detail bias. Positive values make the texture blurrier by picking smaller mipmaps sooner,
+    /// negative values make it sharper.
+    pub fn set_lod_bias(&self, bias: f32) {
+        self.with_bound(|| unsafe {
+            gl::TexParameterf(gl::TEXTURE_2D, gl::TEXTURE_LOD_BIAS, bias);
+        });
+    }
+
+    /// Clamps the level of detail that is used for sampling to the given range.
+    pub fn set_lod_range(&self, min_lod: f32, max_lod: f32) {
+        self.with_bound(|| unsafe {
+            gl::TexParameterf(gl::TEXTURE_2D, gl::TEXTURE_MIN_LOD, min_lod);
+            gl::TexParameterf(gl::TEXTURE_2D, gl::TEXTURE_MAX_LOD, max_lod);
+        });
+    }
+
+    /// Binds the texture to the active texture unit, runs `f` and then restores the previously bound texture.
+    pub(crate) fn with_bound<F: FnOnce()>(&self, f: F) {
+        with_bound_texture(gl::TEXTURE_2D, self.id, f);
     }
 
     /// Returns the width of the texture.
@@ -174,30 +490,49 @@ impl Texture {
         self.height
     }
 
+    /// Returns the format the texture is stored in on the GPU.
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    /// Saves the texture to an image file, the image format is picked from the file extension.
+    /// Float textures keep their full range when saved as `.hdr` or `.exr`, other formats clamp them to 0 to 1.
+    /// Integer textures can not be saved.
     pub fn save_to_file(&self, path: &Path) -> Result<(), String> {
+    if self.format.is_integer() {
+        return Err(format!(
+            "[FerrousGl Error] Can not save a {:?} texture, integer textures have no image file equivalent.",
+            self.format
+        ));
+    }
+
+    if self.format.is_float() {
+        let mut buffer = vec![0f32; (self.width * self.height * 4) as usize];
+
+        self.with_bound(|| unsafe {
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::GetTexImage(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA,
+                gl::FLOAT,
+                buffer.as_mut_ptr() as *mut GLvoid,
+            );
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 4);
+        });
+
+        let image = image::Rgba32FImage::from_raw(self.width, self.height, buffer)
+            .ok_or("Failed to create image from float texture data")?;
+        return save_image(DynamicImage::ImageRgba32F(image), path);
+    }
+
     // Bind the texture
     unsafe {
         gl::BindTexture(gl::TEXTURE_2D, self.id);
     }
 
-    // First try to read as RGBA texture
-    let mut buffer = vec![0u8; (self.width * self.height * 4) as usize];
-    
-    unsafe {
-        gl::GetTexImage(
-            gl::TEXTURE_2D,
-            0,
-            gl::RGBA,
-            gl::UNSIGNED_BYTE,
-            buffer.as_mut_ptr() as *mut GLvoid,
-        );
-    }
-
-    // Check if we got meaningful RGBA data (not all zeros)
-    let is_depth_texture = buffer.iter().all(|&x| x == 0);
-    
-    if is_depth_texture {
-        // If it's a depth texture, read it as depth data
+    if self.format.is_depth() {
+        // Read depth textures as depth data
         let mut depth_buffer = vec![0f32; (self.width * self.height) as usize];
         
         unsafe {
@@ -229,28 +564,93 @@ impl Texture {
 
         // Create and save the image
         match image::RgbaImage::from_raw(self.width, self.height, image_buffer) {
-            Some(image) => {
-                image.save(path).map_err(|e| e.to_string())?;
-                Ok(())
-            }
+            Some(image) => save_image(DynamicImage::ImageRgba8(image), path),
             None => Err("Failed to create image from depth texture data".to_string()),
         }
     } else {
         // Regular RGBA texture
+        let mut buffer = vec![0u8; (self.width * self.height * 4) as usize];
+
         unsafe {
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::GetTexImage(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                buffer.as_mut_ptr() as *mut GLvoid,
+            );
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 4);
+
             // Unbind the texture
             gl::BindTexture(gl::TEXTURE_2D, 0);
         }
 
         match image::RgbaImage::from_raw(self.width, self.height, buffer) {
-            Some(image) => {
-                image.save(path).map_err(|e| e.to_string())?;
-                Ok(())
-            }
+            Some(image) => save_image(DynamicImage::ImageRgba8(image), path),
             None => Err("Failed to create image from texture data".to_string()),
         }
     }
 }
+
+    /// Reads a rectangle of the texture's first level, blocking until the GPU has finished rendering into it.
+    /// Rows start at the bottom of the rectangle. Use `PixelFormat::Depth` or `PixelFormat::Stencil` for depth textures.
+    pub fn read_pixels<T: PixelComponent>(&self, rect: Rect, format: PixelFormat) -> Result<Vec<T>, String> {
+        self.with_read_framebuffer(rect, format, |framebuffer_id| {
+            read_framebuffer(framebuffer_id, None, rect, format)
+        })
+    }
+
+    /// Starts reading a rectangle of the texture's first level in the background, see [`PixelReadback`].
+    pub fn read_pixels_async<T: PixelComponent>(&self, rect: Rect, format: PixelFormat) -> Result<PixelReadback<T>, String> {
+        self.with_read_framebuffer(rect, format, |framebuffer_id| {
+            PixelReadback::start(framebuffer_id, None, rect, format)
+        })
+    }
+
+    /// Attaches the texture to a temporary framebuffer so it can be read with `glReadPixels`.
+    fn with_read_framebuffer<R, F: FnOnce(GLuint) -> R>(&self, rect: Rect, format: PixelFormat, f: F) -> Result<R, String> {
+        if self.format.is_compressed() {
+            return Err(format!(
+                "[FerrousGl Error] Can not read pixels of a compressed {:?} texture.",
+                self.format
+            ));
+        }
+        check_rect(rect, self.width, self.height)?;
+        check_pixel_format(self.format, format)?;
+
+        let mut framebuffer_id = 0;
+
+        unsafe {
+            let mut previous_framebuffer = 0;
+            gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut previous_framebuffer);
+
+            gl::GenFramebuffers(1, &mut framebuffer_id);
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, framebuffer_id);
+
+            let attachment = if self.format.has_stencil() {
+                gl::DEPTH_STENCIL_ATTACHMENT
+            } else if self.format.is_depth() {
+                gl::DEPTH_ATTACHMENT
+            } else {
+                gl::COLOR_ATTACHMENT0
+            };
+            gl::FramebufferTexture2D(gl::READ_FRAMEBUFFER, attachment, gl::TEXTURE_2D, self.id, 0);
+            if self.format.is_depth() {
+                gl::ReadBuffer(gl::NONE);
+            }
+
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, previous_framebuffer as GLuint);
+        }
+
+        let result = f(framebuffer_id);
+
+        unsafe {
+            gl::DeleteFramebuffers(1, &framebuffer_id);
+        }
+
+        Ok(result)
+    }
 }
 
 impl Drop for Texture {
@@ -262,6 +662,178 @@ impl Drop for Texture {
     }
 }
 
+/// Enum tokens from EXT_texture_filter_anisotropic (core since OpenGL 4.6), which are missing from the bindings.
+pub(crate) const TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FE;
+pub(crate) const MAX_TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FF;
+
+/// Enum tokens from EXT_texture_compression_s3tc and EXT_texture_sRGB, which are missing from the bindings.
+const COMPRESSED_RGBA_S3TC_DXT1: GLenum = 0x83F1;
+const COMPRESSED_RGBA_S3TC_DXT3: GLenum = 0x83F2;
+const COMPRESSED_RGBA_S3TC_DXT5: GLenum = 0x83F3;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT1: GLenum = 0x8C4D;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT3: GLenum = 0x8C4E;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT5: GLenum = 0x8C4F;
+
+/// Returns true if the current OpenGL context supports the given extension.
+pub(crate) fn has_gl_extension(name: &str) -> bool {
+    unsafe {
+        let mut count = 0;
+        gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+
+        (0..count as GLuint).any(|i| {
+            let extension = gl::GetStringi(gl::EXTENSIONS, i);
+            !extension.is_null()
+                && std::ffi::CStr::from_ptr(extension as *const std::ffi::c_char).to_bytes()
+                    == name.as_bytes()
+        })
+    }
+}
+
+/// Returns the highest anisotropic filtering level supported by the driver, or 1.0 if it is not supported.
+pub fn max_anisotropy() -> f32 {
+    if !has_gl_extension("GL_EXT_texture_filter_anisotropic")
+        && !has_gl_extension("GL_ARB_texture_filter_anisotropic")
+    {
+        return 1.0;
+    }
+
+    let mut max_level = 1.0;
+    unsafe {
+        gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max_level);
+    }
+    max_level
+}
+
+/// Options used when creating a texture from an image, see [`Texture::from_image_with_options`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureOptions {
+    /// The format the texture is stored in, or `None` to pick it from the image.
+    pub format: Option<TextureFormat>,
+    pub mipmaps: MipmapGeneration,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            format: None,
+            mipmaps: MipmapGeneration::Gpu,
+        }
+    }
+}
+
+/// How the mip levels of a new texture are created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MipmapGeneration {
+    /// Only the full size level is allocated. Mipmaps can still be generated later.
+    None,
+    /// The driver generates mipmaps with `glGenerateMipmap`.
+    Gpu,
+    /// Mipmaps are generated on the CPU, averaging sRGB textures in linear space (see [`crate::mipmap::generate_mip_chain`]).
+    Cpu,
+}
+
+/// Pixel data of an image converted into a texture format, with its CPU generated mip levels.
+/// This is the part of creating a texture that doesn't need a GL context, so it can run on another thread.
+pub(crate) struct PreparedImage {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) format: TextureFormat,
+    /// The pixel data of each level, starting with the full size image.
+    pub(crate) levels: Vec<Vec<u8>>,
+    /// Whether the driver should generate the remaining mip levels after uploading.
+    pub(crate) generate_mipmaps: bool,
+}
+
+impl PreparedImage {
+    pub(crate) fn new(img: &DynamicImage, options: TextureOptions) -> Result<Self, String> {
+        let format = options.format.unwrap_or_else(|| TextureFormat::for_image(img));
+
+        // Integer textures can't be filtered, so they never get mipmaps
+        let mipmaps = if format.is_integer() {
+            MipmapGeneration::None
+        } else {
+            options.mipmaps
+        };
+
+        let mut levels = vec![image_to_bytes(img, format)?];
+        if mipmaps == MipmapGeneration::Cpu {
+            for level in generate_mip_chain(img, format.is_srgb()) {
+                levels.push(image_to_bytes(&level, format)?);
+            }
+        }
+
+        Ok(PreparedImage {
+            width: img.width(),
+            height: img.height(),
+            format,
+            levels,
+            generate_mipmaps: mipmaps == MipmapGeneration::Gpu,
+        })
+    }
+}
+
+/// Returns an error if pixels of the given format can't be read from a texture format.
+pub(crate) fn check_pixel_format(texture_format: TextureFormat, pixel_format: PixelFormat) -> Result<(), String> {
+    let compatible = match pixel_format {
+        PixelFormat::Depth => texture_format.is_depth(),
+        PixelFormat::Stencil => texture_format.has_stencil(),
+        PixelFormat::RedInteger | PixelFormat::RgbaInteger => texture_format.is_integer(),
+        _ => !texture_format.is_depth() && !texture_format.is_integer(),
+    };
+
+    if compatible {
+        Ok(())
+    } else {
+        Err(format!(
+            "[FerrousGl Error] Can not read {:?} pixels from a {:?} texture.",
+            pixel_format, texture_format
+        ))
+    }
+}
+
+/// Returns the minification filter for a combination of mipmap type and base filter. In the GL names the base
+/// filter comes first and the filter between mip levels second.
+pub(crate) fn min_filter(mipmap_type: MipmapType, base_filter: FilterMode) -> GLenum {
+    match (mipmap_type, base_filter) {
+        (MipmapType::None, FilterMode::Linear) => gl::LINEAR,
+        (MipmapType::None, FilterMode::Nearest) => gl::NEAREST,
+        (MipmapType::Nearest, FilterMode::Linear) => gl::LINEAR_MIPMAP_NEAREST,
+        (MipmapType::Nearest, FilterMode::Nearest) => gl::NEAREST_MIPMAP_NEAREST,
+        (MipmapType::Linear, FilterMode::Linear) => gl::LINEAR_MIPMAP_LINEAR,
+        (MipmapType::Linear, FilterMode::Nearest) => gl::NEAREST_MIPMAP_LINEAR,
+    }
+}
+
+/// Binds a texture to `target` on the active texture unit, runs `f` and then restores the previously bound texture.
+pub(crate) fn with_bound_texture<F: FnOnce()>(target: GLenum, texture_id: GLuint, f: F) {
+    let binding = match target {
+        gl::TEXTURE_2D => gl::TEXTURE_BINDING_2D,
+        gl::TEXTURE_2D_ARRAY => gl::TEXTURE_BINDING_2D_ARRAY,
+        gl::TEXTURE_3D => gl::TEXTURE_BINDING_3D,
+        gl::TEXTURE_CUBE_MAP => gl::TEXTURE_BINDING_CUBE_MAP,
+        _ => unreachable!("unsupported texture target {:#x}", target),
+    };
+
+    unsafe {
+        let mut previous = 0;
+        gl::GetIntegerv(binding, &mut previous);
+        gl::BindTexture(target, texture_id);
+
+        f();
+
+        gl::BindTexture(target, previous as GLuint);
+    }
+}
+
+/// Sets the minification and magnification filters of a texture bound to `target`.
+pub(crate) fn set_texture_filtering(target: GLenum, texture_id: GLuint, mipmap_type: MipmapType, base_filter: FilterMode) {
+    with_bound_texture(target, texture_id, || unsafe {
+        gl::TexParameteri(target, gl::TEXTURE_MIN_FILTER, min_filter(mipmap_type, base_filter) as i32);
+        // Mipmaps don't affect magnification
+        gl::TexParameteri(target, gl::TEXTURE_MAG_FILTER, GLenum::from(base_filter) as i32);
+    });
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MipmapType {
     None,
@@ -274,3 +846,560 @@ pub enum FilterMode {
     Linear,
     Nearest,
 }
+
+impl From<FilterMode> for GLenum {
+    fn from(filter: FilterMode) -> Self {
+        match filter {
+            FilterMode::Linear => gl::LINEAR,
+            FilterMode::Nearest => gl::NEAREST,
+        }
+    }
+}
+
+/// Enum representing how texture coordinates outside of the 0 to 1 range are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMode {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+    ClampToBorder,
+    MirrorClampToEdge,
+}
+
+impl From<WrapMode> for GLenum {
+    fn from(wrap: WrapMode) -> Self {
+        match wrap {
+            WrapMode::Repeat => gl::REPEAT,
+            WrapMode::MirroredRepeat => gl::MIRRORED_REPEAT,
+            WrapMode::ClampToEdge => gl::CLAMP_TO_EDGE,
+            WrapMode::ClampToBorder => gl::CLAMP_TO_BORDER,
+            WrapMode::MirrorClampToEdge => gl::MIRROR_CLAMP_TO_EDGE,
+        }
+    }
+}
+
+/// Enum representing the formats a texture can be stored in on the GPU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextureFormat {
+    R8,
+    Rg8,
+    Rgb8,
+    Rgba8,
+    Srgb8,
+    Srgb8Alpha8,
+    Rgba16,
+    R16F,
+    Rg16F,
+    Rgb16F,
+    Rgba16F,
+    R32F,
+    Rg32F,
+    Rgb32F,
+    Rgba32F,
+    R32UI,
+    Depth16,
+    Depth24,
+    Depth32F,
+    Depth24Stencil8,
+    Depth32FStencil8,
+    /// BC1 (DXT1) block compression, RGB with 1-bit alpha.
+    Bc1,
+    Bc1Srgb,
+    /// BC2 (DXT3) block compression, RGBA with sharp alpha.
+    Bc2,
+    Bc2Srgb,
+    /// BC3 (DXT5) block compression, RGBA with smooth alpha.
+    Bc3,
+    Bc3Srgb,
+    /// BC4 (RGTC1) block compression, a single channel.
+    Bc4,
+    Bc4Signed,
+    /// BC5 (RGTC2) block compression, two channels, commonly used for normal maps.
+    Bc5,
+    Bc5Signed,
+    /// BC6H (BPTC) block compression, HDR RGB.
+    Bc6H,
+    Bc6HSigned,
+    /// BC7 (BPTC) block compression, high quality RGBA.
+    Bc7,
+    Bc7Srgb,
+}
+
+impl TextureFormat {
+    /// Returns the format that best preserves the precision of an image.
+    /// 8-bit images use RGBA8, 16-bit images use RGBA16 and float images (such as `.hdr` or `.exr` files) keep their
+    /// 32-bit float channels. Pass `TextureFormat::Rgb16F` explicitly to store float RGB images at half the memory.
+    pub fn for_image(img: &DynamicImage) -> Self {
+        match img.color() {
+            image::ColorType::Rgb32F => TextureFormat::Rgb32F,
+            image::ColorType::Rgba32F => TextureFormat::Rgba32F,
+            image::ColorType::L16
+            | image::ColorType::La16
+            | image::ColorType::Rgb16
+            | image::ColorType::Rgba16 => TextureFormat::Rgba16,
+            _ => TextureFormat::Rgba8,
+        }
+    }
+
+    /// Returns the sized internal format used to store the texture on the GPU.
+    pub fn internal_format(&self) -> GLenum {
+        match self {
+            TextureFormat::R8 => gl::R8,
+            TextureFormat::Rg8 => gl::RG8,
+            TextureFormat::Rgb8 => gl::RGB8,
+            TextureFormat::Rgba8 => gl::RGBA8,
+            TextureFormat::Srgb8 => gl::SRGB8,
+            TextureFormat::Srgb8Alpha8 => gl::SRGB8_ALPHA8,
+            TextureFormat::Rgba16 => gl::RGBA16,
+            TextureFormat::R16F => gl::R16F,
+            TextureFormat::Rg16F => gl::RG16F,
+            TextureFormat::Rgb16F => gl::RGB16F,
+            TextureFormat::Rgba16F => gl::RGBA16F,
+            TextureFormat::R32F => gl::R32F,
+            TextureFormat::Rg32F => gl::RG32F,
+            TextureFormat::Rgb32F => gl::RGB32F,
+            TextureFormat::Rgba32F => gl::RGBA32F,
+            TextureFormat::R32UI => gl::R32UI,
+            TextureFormat::Depth16 => gl::DEPTH_COMPONENT16,
+            TextureFormat::Depth24 => gl::DEPTH_COMPONENT24,
+            TextureFormat::Depth32F => gl::DEPTH_COMPONENT32F,
+            TextureFormat::Depth24Stencil8 => gl::DEPTH24_STENCIL8,
+            TextureFormat::Depth32FStencil8 => gl::DEPTH32F_STENCIL8,
+            TextureFormat::Bc1 => COMPRESSED_RGBA_S3TC_DXT1,
+            TextureFormat::Bc1Srgb => COMPRESSED_SRGB_ALPHA_S3TC_DXT1,
+            TextureFormat::Bc2 => COMPRESSED_RGBA_S3TC_DXT3,
+            TextureFormat::Bc2Srgb => COMPRESSED_SRGB_ALPHA_S3TC_DXT3,
+            TextureFormat::Bc3 => COMPRESSED_RGBA_S3TC_DXT5,
+            TextureFormat::Bc3Srgb => COMPRESSED_SRGB_ALPHA_S3TC_DXT5,
+            TextureFormat::Bc4 => gl::COMPRESSED_RED_RGTC1,
+            TextureFormat::Bc4Signed => gl::COMPRESSED_SIGNED_RED_RGTC1,
+            TextureFormat::Bc5 => gl::COMPRESSED_RG_RGTC2,
+            TextureFormat::Bc5Signed => gl::COMPRESSED_SIGNED_RG_RGTC2,
+            TextureFormat::Bc6H => gl::COMPRESSED_RGB_BPTC_UNSIGNED_FLOAT,
+            TextureFormat::Bc6HSigned => gl::COMPRESSED_RGB_BPTC_SIGNED_FLOAT,
+            TextureFormat::Bc7 => gl::COMPRESSED_RGBA_BPTC_UNORM,
+            TextureFormat::Bc7Srgb => gl::COMPRESSED_SRGB_ALPHA_BPTC_UNORM,
+        }
+    }
+
+    /// Returns the layout of the pixel data used when uploading or reading back the texture.
+    /// Compressed formats return the layout they are decompressed to when read back.
+    pub fn pixel_format(&self) -> GLenum {
+        match self {
+            TextureFormat::R8
+            | TextureFormat::R16F
+            | TextureFormat::R32F
+            | TextureFormat::Bc4
+            | TextureFormat::Bc4Signed => gl::RED,
+            TextureFormat::Rg8
+            | TextureFormat::Rg16F
+            | TextureFormat::Rg32F
+            | TextureFormat::Bc5
+            | TextureFormat::Bc5Signed => gl::RG,
+            TextureFormat::Rgb8
+            | TextureFormat::Srgb8
+            | TextureFormat::Rgb16F
+            | TextureFormat::Rgb32F
+            | TextureFormat::Bc6H
+            | TextureFormat::Bc6HSigned => gl::RGB,
+            TextureFormat::Rgba8
+            | TextureFormat::Srgb8Alpha8
+            | TextureFormat::Rgba16
+            | TextureFormat::Rgba16F
+            | TextureFormat::Rgba32F
+            | TextureFormat::Bc1
+            | TextureFormat::Bc1Srgb
+            | TextureFormat::Bc2
+            | TextureFormat::Bc2Srgb
+            | TextureFormat::Bc3
+            | TextureFormat::Bc3Srgb
+            | TextureFormat::Bc7
+            | TextureFormat::Bc7Srgb => gl::RGBA,
+            TextureFormat::R32UI => gl::RED_INTEGER,
+            TextureFormat::Depth16 | TextureFormat::Depth24 | TextureFormat::Depth32F => {
+                gl::DEPTH_COMPONENT
+            }
+            TextureFormat::Depth24Stencil8 | TextureFormat::Depth32FStencil8 => gl::DEPTH_STENCIL,
+        }
+    }
+
+    /// Returns the component type of the pixel data used when uploading or reading back the texture.
+    pub fn pixel_type(&self) -> GLenum {
+        match self {
+            TextureFormat::R8
+            | TextureFormat::Rg8
+            | TextureFormat::Rgb8
+            | TextureFormat::Rgba8
+            | TextureFormat::Srgb8
+            | TextureFormat::Srgb8Alpha8
+            | TextureFormat::Bc1
+            | TextureFormat::Bc1Srgb
+            | TextureFormat::Bc2
+            | TextureFormat::Bc2Srgb
+            | TextureFormat::Bc3
+            | TextureFormat::Bc3Srgb
+            | TextureFormat::Bc4
+            | TextureFormat::Bc5
+            | TextureFormat::Bc7
+            | TextureFormat::Bc7Srgb => gl::UNSIGNED_BYTE,
+            TextureFormat::Bc4Signed | TextureFormat::Bc5Signed => gl::BYTE,
+            TextureFormat::Rgba16 | TextureFormat::Depth16 => gl::UNSIGNED_SHORT,
+            TextureFormat::R16F
+            | TextureFormat::Rg16F
+            | TextureFormat::Rgb16F
+            | TextureFormat::Rgba16F
+            | TextureFormat::R32F
+            | TextureFormat::Rg32F
+            | TextureFormat::Rgb32F
+            | TextureFormat::Rgba32F
+            | TextureFormat::Bc6H
+            | TextureFormat::Bc6HSigned
+            | TextureFormat::Depth32F => gl::FLOAT,
+            TextureFormat::R32UI | TextureFormat::Depth24 => gl::UNSIGNED_INT,
+            TextureFormat::Depth24Stencil8 => gl::UNSIGNED_INT_24_8,
+            TextureFormat::Depth32FStencil8 => gl::FLOAT_32_UNSIGNED_INT_24_8_REV,
+        }
+    }
+
+    /// Returns the number of components (channels) per pixel.
+    pub fn components(&self) -> usize {
+        match self.pixel_format() {
+            gl::RG => 2,
+            gl::RGB => 3,
+            gl::RGBA => 4,
+            _ => 1,
+        }
+    }
+
+    /// Returns the size in bytes of a single pixel in the layout given by `pixel_format` and `pixel_type`.
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self.pixel_type() {
+            gl::UNSIGNED_BYTE | gl::BYTE => self.components(),
+            gl::UNSIGNED_SHORT => self.components() * 2,
+            gl::FLOAT_32_UNSIGNED_INT_24_8_REV => 8,
+            _ => self.components() * 4,
+        }
+    }
+
+    /// Returns true if this is a depth or depth-stencil format.
+    pub fn is_depth(&self) -> bool {
+        matches!(
+            self,
+            TextureFormat::Depth16
+                | TextureFormat::Depth24
+                | TextureFormat::Depth32F
+                | TextureFormat::Depth24Stencil8
+                | TextureFormat::Depth32FStencil8
+        )
+    }
+
+    /// Returns true if this format also contains a stencil component.
+    pub fn has_stencil(&self) -> bool {
+        matches!(self, TextureFormat::Depth24Stencil8 | TextureFormat::Depth32FStencil8)
+    }
+
+    /// Returns true if this is a block compressed format, which can only be uploaded as precompressed data.
+    pub fn is_compressed(&self) -> bool {
+        self.block_size().is_some()
+    }
+
+    /// Returns the size in bytes of one compressed 4x4 block, or `None` for uncompressed formats.
+    pub fn block_size(&self) -> Option<usize> {
+        match self {
+            TextureFormat::Bc1
+            | TextureFormat::Bc1Srgb
+            | TextureFormat::Bc4
+            | TextureFormat::Bc4Signed => Some(8),
+            TextureFormat::Bc2
+            | TextureFormat::Bc2Srgb
+            | TextureFormat::Bc3
+            | TextureFormat::Bc3Srgb
+            | TextureFormat::Bc5
+            | TextureFormat::Bc5Signed
+            | TextureFormat::Bc6H
+            | TextureFormat::Bc6HSigned
+            | TextureFormat::Bc7
+            | TextureFormat::Bc7Srgb => Some(16),
+            _ => None,
+        }
+    }
+
+    /// Returns an error if the driver can not sample this format, which can happen for compressed formats
+    /// that are provided by extensions instead of core OpenGL 3.3.
+    pub fn check_support(&self) -> Result<(), String> {
+        let extensions: &[&str] = match self {
+            TextureFormat::Bc1 | TextureFormat::Bc2 | TextureFormat::Bc3 => {
+                &["GL_EXT_texture_compression_s3tc"]
+            }
+            TextureFormat::Bc1Srgb | TextureFormat::Bc2Srgb | TextureFormat::Bc3Srgb => {
+                &["GL_EXT_texture_compression_s3tc_srgb", "GL_EXT_texture_sRGB"]
+            }
+            TextureFormat::Bc6H
+            | TextureFormat::Bc6HSigned
+            | TextureFormat::Bc7
+            | TextureFormat::Bc7Srgb => &["GL_ARB_texture_compression_bptc"],
+            _ => return Ok(()),
+        };
+
+        if extensions.iter().any(|name| has_gl_extension(name)) {
+            Ok(())
+        } else {
+            Err(format!(
+                "[FerrousGl Error] {:?} textures need the {} extension, which is not supported by this driver.",
+                self,
+                extensions.join(" or ")
+            ))
+        }
+    }
+
+    /// Returns true if the color channels of this format are stored in sRGB and converted to linear when sampled.
+    pub fn is_srgb(&self) -> bool {
+        matches!(
+            self,
+            TextureFormat::Srgb8
+                | TextureFormat::Srgb8Alpha8
+                | TextureFormat::Bc1Srgb
+                | TextureFormat::Bc2Srgb
+                | TextureFormat::Bc3Srgb
+                | TextureFormat::Bc7Srgb
+        )
+    }
+
+    /// Returns true if this format stores unnormalized integers, which have to be sampled with a `usampler`.
+    pub fn is_integer(&self) -> bool {
+        matches!(self, TextureFormat::R32UI)
+    }
+
+    /// Returns true if this format stores floating point color data.
+    pub fn is_float(&self) -> bool {
+        !self.is_depth() && self.pixel_type() == gl::FLOAT
+    }
+
+    /// Integer and depth textures can not be linearly filtered by default, so they start out with nearest filtering.
+    pub(crate) fn default_filter(&self) -> GLenum {
+        if self.is_integer() || self.is_depth() {
+            gl::NEAREST
+        } else {
+            gl::LINEAR
+        }
+    }
+}
+
+/// Uploads pixel data (or allocates storage if `data` is null) for one level of the currently bound texture.
+pub(crate) unsafe fn upload_2d(
+    target: GLenum,
+    level: i32,
+    format: TextureFormat,
+    width: u32,
+    height: u32,
+    data: *const GLvoid,
+) {
+    // Rows of R8 and RGB8 data are not always 4-byte aligned
+    gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+    gl::TexImage2D(
+        target,
+        level,
+        format.internal_format() as i32,
+        width as i32,
+        height as i32,
+        0,
+        format.pixel_format(),
+        format.pixel_type(),
+        data,
+    );
+    gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+}
+
+/// Uploads pixel data (or allocates storage if `data` is null) for one level of the currently bound 3D texture or texture array.
+pub(crate) unsafe fn upload_3d(
+    target: GLenum,
+    level: i32,
+    format: TextureFormat,
+    width: u32,
+    height: u32,
+    depth: u32,
+    data: *const GLvoid,
+) {
+    gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+    gl::TexImage3D(
+        target,
+        level,
+        format.internal_format() as i32,
+        width as i32,
+        height as i32,
+        depth as i32,
+        0,
+        format.pixel_format(),
+        format.pixel_type(),
+        data,
+    );
+    gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+}
+
+/// Uploads pixel data into a box of the currently bound 3D texture or texture array, where `z` selects the slice or layer.
+pub(crate) unsafe fn update_3d(
+    target: GLenum,
+    format: TextureFormat,
+    offset: (u32, u32, u32),
+    size: (u32, u32, u32),
+    data: *const GLvoid,
+) {
+    gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+    gl::TexSubImage3D(
+        target,
+        0,
+        offset.0 as i32,
+        offset.1 as i32,
+        offset.2 as i32,
+        size.0 as i32,
+        size.1 as i32,
+        size.2 as i32,
+        format.pixel_format(),
+        format.pixel_type(),
+        data,
+    );
+    gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+}
+
+/// Saves an image, converting it to what the file format expects. Radiance `.hdr` files only store float RGB,
+/// OpenEXR files store float RGBA and all other formats are written as 8-bit RGBA.
+pub(crate) fn save_image(image: DynamicImage, path: &Path) -> Result<(), String> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase());
+
+    let image = match extension.as_deref() {
+        Some("hdr") => DynamicImage::ImageRgb32F(image.to_rgb32f()),
+        Some("exr") => DynamicImage::ImageRgba32F(image.to_rgba32f()),
+        _ => DynamicImage::ImageRgba8(image.to_rgba8()),
+    };
+
+    image.save(path).map_err(|e| e.to_string())
+}
+
+/// Converts an image into tightly packed pixel data matching the given texture format.
+pub(crate) fn image_to_bytes(img: &DynamicImage, format: TextureFormat) -> Result<Vec<u8>, String> {
+    let data = match format {
+        TextureFormat::R8 => img.to_luma8().into_raw(),
+        TextureFormat::Rg8 => img.to_luma_alpha8().into_raw(),
+        TextureFormat::Rgb8 | TextureFormat::Srgb8 => img.to_rgb8().into_raw(),
+        TextureFormat::Rgba8 | TextureFormat::Srgb8Alpha8 => img.to_rgba8().into_raw(),
+        TextureFormat::Rgba16 => img
+            .to_rgba16()
+            .into_raw()
+            .iter()
+            .flat_map(|v| v.to_ne_bytes())
+            .collect(),
+        TextureFormat::R16F | TextureFormat::R32F => img
+            .to_luma32f()
+            .into_raw()
+            .iter()
+            .flat_map(|v| v.to_ne_bytes())
+            .collect(),
+        TextureFormat::Rg16F | TextureFormat::Rg32F => img
+            .to_luma_alpha32f()
+            .into_raw()
+            .iter()
+            .flat_map(|v| v.to_ne_bytes())
+            .collect(),
+        TextureFormat::Rgb16F | TextureFormat::Rgb32F => img
+            .to_rgb32f()
+            .into_raw()
+            .iter()
+            .flat_map(|v| v.to_ne_bytes())
+            .collect(),
+        TextureFormat::Rgba16F | TextureFormat::Rgba32F => img
+            .to_rgba32f()
+            .into_raw()
+            .iter()
+            .flat_map(|v| v.to_ne_bytes())
+            .collect(),
+        TextureFormat::R32UI => img
+            .to_luma16()
+            .into_raw()
+            .iter()
+            .flat_map(|&v| (v as u32).to_ne_bytes())
+            .collect(),
+        _ => {
+            return Err(format!(
+                "[FerrousGl Error] Can not create a {:?} texture from an image.",
+                format
+            ))
+        }
+    };
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_filter_names_base_filter_first() {
+        assert_eq!(min_filter(MipmapType::None, FilterMode::Linear), gl::LINEAR);
+        assert_eq!(min_filter(MipmapType::None, FilterMode::Nearest), gl::NEAREST);
+        assert_eq!(min_filter(MipmapType::Nearest, FilterMode::Linear), gl::LINEAR_MIPMAP_NEAREST);
+        assert_eq!(min_filter(MipmapType::Nearest, FilterMode::Nearest), gl::NEAREST_MIPMAP_NEAREST);
+        assert_eq!(min_filter(MipmapType::Linear, FilterMode::Linear), gl::LINEAR_MIPMAP_LINEAR);
+        assert_eq!(min_filter(MipmapType::Linear, FilterMode::Nearest), gl::NEAREST_MIPMAP_LINEAR);
+    }
+
+    #[test]
+    fn bytes_per_pixel_matches_upload_layout() {
+        let expected = [
+            (TextureFormat::R8, 1),
+            (TextureFormat::Rg8, 2),
+            (TextureFormat::Rgb8, 3),
+            (TextureFormat::Rgba8, 4),
+            (TextureFormat::Srgb8Alpha8, 4),
+            (TextureFormat::Rgba16, 8),
+            (TextureFormat::R16F, 4),
+            (TextureFormat::Rgba16F, 16),
+            (TextureFormat::Rgb32F, 12),
+            (TextureFormat::Rgba32F, 16),
+            (TextureFormat::R32UI, 4),
+            (TextureFormat::Depth16, 2),
+            (TextureFormat::Depth24, 4),
+            (TextureFormat::Depth32F, 4),
+            (TextureFormat::Depth24Stencil8, 4),
+            (TextureFormat::Depth32FStencil8, 8),
+        ];
+
+        for (format, bytes) in expected {
+            assert_eq!(format.bytes_per_pixel(), bytes, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn for_image_keeps_precision() {
+        assert_eq!(TextureFormat::for_image(&DynamicImage::new_rgb8(1, 1)), TextureFormat::Rgba8);
+        assert_eq!(TextureFormat::for_image(&DynamicImage::new_rgba16(1, 1)), TextureFormat::Rgba16);
+        assert_eq!(TextureFormat::for_image(&DynamicImage::new_rgb32f(1, 1)), TextureFormat::Rgb32F);
+        assert_eq!(TextureFormat::for_image(&DynamicImage::new_rgba32f(1, 1)), TextureFormat::Rgba32F);
+    }
+
+    #[test]
+    fn block_size_of_compressed_formats() {
+        for format in [TextureFormat::Bc1, TextureFormat::Bc1Srgb, TextureFormat::Bc4, TextureFormat::Bc4Signed] {
+            assert_eq!(format.block_size(), Some(8), "{:?}", format);
+        }
+        for format in [
+            TextureFormat::Bc2,
+            TextureFormat::Bc3Srgb,
+            TextureFormat::Bc5,
+            TextureFormat::Bc6H,
+            TextureFormat::Bc6HSigned,
+            TextureFormat::Bc7,
+            TextureFormat::Bc7Srgb,
+        ] {
+            assert_eq!(format.block_size(), Some(16), "{:?}", format);
+        }
+
+        for format in [TextureFormat::Rgba8, TextureFormat::Rgba32F, TextureFormat::R32UI, TextureFormat::Depth24] {
+            assert_eq!(format.block_size(), None, "{:?}", format);
+            assert!(!format.is_compressed());
+        }
+    }
+}
@@ -0,0 +1,201 @@
+extern crate gl;
+extern crate image;
+
+use gl::types::*;
+use image::DynamicImage;
+use std::ptr;
+
+use crate::readback::fits_inside;
+use crate::texture::{image_to_bytes, set_texture_filtering, update_3d, upload_3d, with_bound_texture, FilterMode, MipmapType, TextureFormat, WrapMode};
+
+/// Represents an OpenGL 3D (volume) texture, sampled with a `sampler3D` and three coordinates.
+/// Useful for voxel data, volumetric effects or color grading lookup tables.
+pub struct Texture3D {
+    pub(crate) id: GLuint,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) depth: u32,
+    pub(crate) format: TextureFormat,
+}
+
+impl Texture3D {
+    /// Creates a new 3D texture from in-memory images, one depth slice per image. All images have to be the same size.
+    /// The texture format is picked from the first image and mipmaps are generated.
+    pub fn from_slices(images: &[DynamicImage]) -> Result<Self, String> {
+        let first = images
+            .first()
+            .ok_or("[FerrousGl Error] A 3D texture needs at least one slice!")?;
+
+        let texture = Self::new_empty(
+            first.width(),
+            first.height(),
+            images.len() as u32,
+            TextureFormat::for_image(first),
+        )?;
+
+        for (slice, img) in images.iter().enumerate() {
+            texture.update_slice(slice as u32, img)?;
+        }
+
+        texture.generate_mipmaps();
+
+        Ok(texture)
+    }
+
+    /// Creates a new 3D texture from raw pixel data. Slices follow each other, each laid out as in [`crate::Texture::from_raw`].
+    pub fn from_raw(width: u32, height: u32, depth: u32, format: TextureFormat, data: &[u8]) -> Result<Self, String> {
+        let texture = Self::new_empty(width, height, depth, format)?;
+        texture.update_region((0, 0, 0), (width, height, depth), data)?;
+        Ok(texture)
+    }
+
+    /// Creates an empty 3D texture with the specified size and texture format.
+    pub fn new_empty(width: u32, height: u32, depth: u32, format: TextureFormat) -> Result<Self, String> {
+        let mut texture_id = 0;
+
+        unsafe {
+            gl::GenTextures(1, &mut texture_id);
+            gl::BindTexture(gl::TEXTURE_3D, texture_id);
+
+            upload_3d(gl::TEXTURE_3D, 0, format, width, height, depth, ptr::null());
+
+            let filter = format.default_filter();
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_WRAP_R, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_MIN_FILTER, filter as i32);
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_MAG_FILTER, filter as i32);
+
+            gl::BindTexture(gl::TEXTURE_3D, 0);
+        }
+
+        Ok(Texture3D {
+            id: texture_id,
+            width,
+            height,
+            depth,
+            format,
+        })
+    }
+
+    /// Replaces a single depth slice with an image of the same size, converted into the texture's format.
+    /// Mipmaps are not updated automatically, call [`Texture3D::generate_mipmaps`] afterwards if needed.
+    pub fn update_slice(&self, slice: u32, img: &DynamicImage) -> Result<(), String> {
+        if img.width() != self.width || img.height() != self.height {
+            return Err(format!(
+                "[FerrousGl Error] Slice image is {}x{}, but the 3D texture is {}x{}.",
+                img.width(),
+                img.height(),
+                self.width,
+                self.height
+            ));
+        }
+
+        let data = image_to_bytes(img, self.format)?;
+        self.update_region((0, 0, slice), (self.width, self.height, 1), &data)
+    }
+
+    /// Replaces a box of the texture with raw pixel data, starting at `offset` (x, y, z) with the given `size`.
+    pub fn update_region(&self, offset: (u32, u32, u32), size: (u32, u32, u32), data: &[u8]) -> Result<(), String> {
+        if !fits_inside(
+            [offset.0, offset.1, offset.2],
+            [size.0, size.1, size.2],
+            [self.width, self.height, self.depth],
+        ) {
+            return Err(format!(
+                "[FerrousGl Error] Region {:?} at {:?} is outside of the {}x{}x{} texture.",
+                size, offset, self.width, self.height, self.depth
+            ));
+        }
+
+        let expected_len = size.0 as usize * size.1 as usize * size.2 as usize * self.format.bytes_per_pixel();
+        if data.len() != expected_len {
+            return Err(format!(
+                "[FerrousGl Error] Expected {} bytes of {:?} pixel data, but got {}.",
+                expected_len,
+                self.format,
+                data.len()
+            ));
+        }
+
+        self.with_bound(|| unsafe {
+            update_3d(gl::TEXTURE_3D, self.format, offset, size, data.as_ptr() as *const GLvoid);
+        });
+
+        Ok(())
+    }
+
+    /// Generates mipmaps from the first level.
+    pub fn generate_mipmaps(&self) {
+        if self.format.is_integer() {
+            return;
+        }
+
+        self.with_bound(|| unsafe {
+            gl::GenerateMipmap(gl::TEXTURE_3D);
+        });
+    }
+
+    /// Sets the preferred Texture Mipmap Type or Texture Filtering Mode such as None, Linear and Nearest.
+    pub fn set_mipmap_and_filtering(&self, mipmap_type: MipmapType, base_filter: FilterMode) {
+        set_texture_filtering(gl::TEXTURE_3D, self.id, mipmap_type, base_filter);
+    }
+
+    /// Sets how texture coordinates outside of the 0 to 1 range are handled on each axis (s, t and r).
+    pub fn set_wrap(&self, wrap_s: WrapMode, wrap_t: WrapMode, wrap_r: WrapMode) {
+        self.with_bound(|| unsafe {
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_WRAP_S, GLenum::from(wrap_s) as i32);
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_WRAP_T, GLenum::from(wrap_t) as i32);
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_WRAP_R, GLenum::from(wrap_r) as i32);
+        });
+    }
+
+    /// Binds the 3D texture to a specific texture unit which can be used to set a uniform texture.
+    pub fn bind(&self, texture_unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + texture_unit);
+            gl::BindTexture(gl::TEXTURE_3D, self.id);
+        }
+    }
+
+    /// Unbinds the 3D texture.
+    pub fn unbind(&self) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_3D, 0);
+        }
+    }
+
+    /// Returns the width of the texture.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of the texture.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the depth (number of slices) of the texture.
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// Returns the format the texture is stored in on the GPU.
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    /// Binds the 3D texture to the active texture unit, runs `f` and then restores the previously bound one.
+    fn with_bound<F: FnOnce()>(&self, f: F) {
+        with_bound_texture(gl::TEXTURE_3D, self.id, f);
+    }
+}
+
+impl Drop for Texture3D {
+    /// Cleans up the 3D texture when it goes out of scope.
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
+    }
+}
@@ -0,0 +1,205 @@
+extern crate gl;
+extern crate image;
+
+use gl::types::*;
+use image::DynamicImage;
+use std::path::Path;
+use std::ptr;
+
+use crate::readback::fits_inside;
+use crate::texture::{image_to_bytes, set_texture_filtering, update_3d, upload_3d, with_bound_texture, FilterMode, MipmapType, TextureFormat, WrapMode};
+
+/// Represents an OpenGL 2D texture array, a stack of same-sized 2D layers sampled with a `sampler2DArray` and a
+/// layer index. Useful for terrain splatting or sprite animations without switching textures.
+pub struct TextureArray {
+    pub(crate) id: GLuint,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) layers: u32,
+    pub(crate) format: TextureFormat,
+}
+
+impl TextureArray {
+    /// Creates a new texture array from image files, one layer per file. All images have to be the same size.
+    pub fn new_from_files(paths: &[&Path]) -> Result<Self, String> {
+        let mut images = Vec::with_capacity(paths.len());
+        for path in paths {
+            images.push(image::open(path).map_err(|e| e.to_string())?);
+        }
+
+        Self::from_images(&images)
+    }
+
+    /// Creates a new texture array from in-memory images, one layer per image. All images have to be the same size.
+    /// The texture format is picked from the first image and mipmaps are generated.
+    pub fn from_images(images: &[DynamicImage]) -> Result<Self, String> {
+        let first = images
+            .first()
+            .ok_or("[FerrousGl Error] A texture array needs at least one image!")?;
+
+        let texture_array = Self::new_empty(
+            first.width(),
+            first.height(),
+            images.len() as u32,
+            TextureFormat::for_image(first),
+        )?;
+
+        for (layer, img) in images.iter().enumerate() {
+            texture_array.update_layer(layer as u32, img)?;
+        }
+
+        texture_array.generate_mipmaps();
+
+        Ok(texture_array)
+    }
+
+    /// Creates an empty texture array with the specified layer size, number of layers and texture format.
+    pub fn new_empty(width: u32, height: u32, layers: u32, format: TextureFormat) -> Result<Self, String> {
+        let mut texture_id = 0;
+
+        unsafe {
+            gl::GenTextures(1, &mut texture_id);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, texture_id);
+
+            upload_3d(gl::TEXTURE_2D_ARRAY, 0, format, width, height, layers, ptr::null());
+
+            let filter = format.default_filter();
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_MIN_FILTER, filter as i32);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_MAG_FILTER, filter as i32);
+
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, 0);
+        }
+
+        Ok(TextureArray {
+            id: texture_id,
+            width,
+            height,
+            layers,
+            format,
+        })
+    }
+
+    /// Replaces a single layer with an image of the same size, converted into the array's texture format.
+    /// Mipmaps are not updated automatically, call [`TextureArray::generate_mipmaps`] afterwards if needed.
+    pub fn update_layer(&self, layer: u32, img: &DynamicImage) -> Result<(), String> {
+        if img.width() != self.width || img.height() != self.height {
+            return Err(format!(
+                "[FerrousGl Error] Layer image is {}x{}, but the texture array is {}x{}.",
+                img.width(),
+                img.height(),
+                self.width,
+                self.height
+            ));
+        }
+
+        let data = image_to_bytes(img, self.format)?;
+        self.update_layer_raw(layer, &data)
+    }
+
+    /// Replaces a single layer with raw pixel data, laid out as in [`crate::Texture::from_raw`].
+    pub fn update_layer_raw(&self, layer: u32, data: &[u8]) -> Result<(), String> {
+        if !fits_inside([layer], [1], [self.layers]) {
+            return Err(format!(
+                "[FerrousGl Error] Layer {} is out of range, the texture array has {} layers.",
+                layer, self.layers
+            ));
+        }
+
+        let expected_len = self.width as usize * self.height as usize * self.format.bytes_per_pixel();
+        if data.len() != expected_len {
+            return Err(format!(
+                "[FerrousGl Error] Expected {} bytes of {:?} pixel data, but got {}.",
+                expected_len,
+                self.format,
+                data.len()
+            ));
+        }
+
+        self.with_bound(|| unsafe {
+            update_3d(
+                gl::TEXTURE_2D_ARRAY,
+                self.format,
+                (0, 0, layer),
+                (self.width, self.height, 1),
+                data.as_ptr() as *const GLvoid,
+            );
+        });
+
+        Ok(())
+    }
+
+    /// Generates mipmaps for every layer from their first level.
+    pub fn generate_mipmaps(&self) {
+        if self.format.is_integer() {
+            return;
+        }
+
+        self.with_bound(|| unsafe {
+            gl::GenerateMipmap(gl::TEXTURE_2D_ARRAY);
+        });
+    }
+
+    /// Sets the preferred Texture Mipmap Type or Texture Filtering Mode such as None, Linear and Nearest.
+    pub fn set_mipmap_and_filtering(&self, mipmap_type: MipmapType, base_filter: FilterMode) {
+        set_texture_filtering(gl::TEXTURE_2D_ARRAY, self.id, mipmap_type, base_filter);
+    }
+
+    /// Sets how texture coordinates outside of the 0 to 1 range are handled, horizontally (s) and vertically (t).
+    pub fn set_wrap(&self, wrap_s: WrapMode, wrap_t: WrapMode) {
+        self.with_bound(|| unsafe {
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_S, GLenum::from(wrap_s) as i32);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_T, GLenum::from(wrap_t) as i32);
+        });
+    }
+
+    /// Binds the texture array to a specific texture unit which can be used to set a uniform texture.
+    pub fn bind(&self, texture_unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + texture_unit);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.id);
+        }
+    }
+
+    /// Unbinds the texture array.
+    pub fn unbind(&self) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, 0);
+        }
+    }
+
+    /// Returns the width of each layer.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of each layer.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the number of layers.
+    pub fn layers(&self) -> u32 {
+        self.layers
+    }
+
+    /// Returns the format the layers are stored in on the GPU.
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    /// Binds the texture array to the active texture unit, runs `f` and then restores the previously bound one.
+    fn with_bound<F: FnOnce()>(&self, f: F) {
+        with_bound_texture(gl::TEXTURE_2D_ARRAY, self.id, f);
+    }
+}
+
+impl Drop for TextureArray {
+    /// Cleans up the texture array when it goes out of scope.
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
+    }
+}
@@ -0,0 +1,420 @@
+extern crate image;
+
+use glam::Vec2;
+use image::{DynamicImage, GenericImageView, RgbaImage};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+#[cfg(feature = "serde")]
+use std::fs;
+use std::path::Path;
+
+use crate::Texture;
+
+/// A named rectangle inside a texture atlas, in pixels and in texture coordinates.
+/// Like [`Texture::from_image`], the top row of the atlas image is at v = 0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AtlasRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Texture coordinates are not stored in layout files, they are recalculated from the atlas size when loading.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub uv_min: Vec2,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub uv_max: Vec2,
+}
+
+impl AtlasRegion {
+    fn new(x: u32, y: u32, width: u32, height: u32, atlas_width: u32, atlas_height: u32) -> Self {
+        let atlas_size = Vec2::new(atlas_width as f32, atlas_height as f32);
+        AtlasRegion {
+            x,
+            y,
+            width,
+            height,
+            uv_min: Vec2::new(x as f32, y as f32) / atlas_size,
+            uv_max: Vec2::new((x + width) as f32, (y + height) as f32) / atlas_size,
+        }
+    }
+}
+
+/// The size of an atlas and where each named image has been placed in it.
+/// With the `serde` feature, layouts can be exported to and imported from JSON, so atlases can be packed ahead of time.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AtlasLayout {
+    pub width: u32,
+    pub height: u32,
+    pub regions: BTreeMap<String, AtlasRegion>,
+}
+
+#[cfg(feature = "serde")]
+impl AtlasLayout {
+    /// Loads a layout from a JSON file written by [`AtlasLayout::save`].
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = fs::read_to_string(path)
+            .map_err(|e| format!("[FerrousGl Error] Failed to read atlas layout file: {}", e))?;
+        Self::from_json(&json)
+    }
+
+    /// Saves the layout as a JSON file.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.to_json())
+            .map_err(|e| format!("[FerrousGl Error] Failed to write atlas layout file: {}", e))
+    }
+
+    /// Converts the layout to JSON, in the form
+    /// `{"width": 256, "height": 256, "regions": {"name": {"x": 0, "y": 0, "width": 16, "height": 16}}}`.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("[FerrousGl Error] Failed to convert atlas layout to JSON.")
+    }
+
+    /// Parses a layout from JSON written by [`AtlasLayout::to_json`]. Texture coordinates are recalculated.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let mut layout: AtlasLayout = serde_json::from_str(json)
+            .map_err(|e| format!("[FerrousGl Error] Invalid atlas layout JSON: {}", e))?;
+
+        let (width, height) = (layout.width, layout.height);
+        for region in layout.regions.values_mut() {
+            *region = AtlasRegion::new(region.x, region.y, region.width, region.height, width, height);
+        }
+
+        Ok(layout)
+    }
+}
+
+/// Packs many images into a single texture. Images are sorted by height and placed with a skyline
+/// (bottom-left) algorithm, with optional padding between them and edge extrusion, which repeats the border
+/// pixels of each image outwards so filtering and mipmapping don't bleed neighbouring images into each other.
+pub struct TextureAtlasBuilder {
+    max_width: u32,
+    max_height: u32,
+    padding: u32,
+    extrude: u32,
+    images: Vec<(String, DynamicImage)>,
+}
+
+impl TextureAtlasBuilder {
+    /// Creates a new atlas builder. The packed atlas will be at most `max_width` by `max_height` pixels.
+    pub fn new(max_width: u32, max_height: u32) -> Self {
+        TextureAtlasBuilder {
+            max_width,
+            max_height,
+            padding: 0,
+            extrude: 0,
+            images: Vec::new(),
+        }
+    }
+
+    /// Sets the number of empty pixels left between images.
+    pub fn padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Sets the number of pixels the border of each image is repeated outwards.
+    pub fn extrude(mut self, extrude: u32) -> Self {
+        self.extrude = extrude;
+        self
+    }
+
+    /// Adds a named image to the atlas. Adding an image with a name that is already used replaces it.
+    pub fn add_image(mut self, name: &str, image: DynamicImage) -> Self {
+        self.images.retain(|(existing, _)| existing != name);
+        self.images.push((name.to_string(), image));
+        self
+    }
+
+    /// Adds a named image file to the atlas.
+    pub fn add_image_file(self, name: &str, path: &Path) -> Result<Self, String> {
+        let image = image::open(path).map_err(|e| e.to_string())?;
+        Ok(self.add_image(name, image))
+    }
+
+    /// Packs all images and uploads the result as a single texture.
+    pub fn build(&self) -> Result<TextureAtlas, String> {
+        let (image, layout) = self.build_image()?;
+        let texture = Texture::from_image(&DynamicImage::ImageRgba8(image))?;
+        Ok(TextureAtlas { texture, layout })
+    }
+
+    /// Packs all images without uploading them, returning the atlas image and its layout.
+    /// Save both to prebuild an atlas, and load it later with [`TextureAtlas::load`].
+    pub fn build_image(&self) -> Result<(RgbaImage, AtlasLayout), String> {
+        let border = self.extrude * 2 + self.padding;
+
+        let mut order: Vec<usize> = (0..self.images.len()).collect();
+        order.sort_by_key(|&i| {
+            let image = &self.images[i].1;
+            (std::cmp::Reverse(image.height()), std::cmp::Reverse(image.width()))
+        });
+
+        let mut skyline = vec![SkylineSegment {
+            x: 0,
+            y: 0,
+            width: self.max_width,
+        }];
+        let mut placements = vec![(0, 0); self.images.len()];
+        let (mut used_width, mut used_height) = (0, 0);
+
+        for &i in &order {
+            let (name, image) = &self.images[i];
+            let width = image.width() + border;
+            let height = image.height() + border;
+
+            let (index, x, y) = find_skyline_position(&skyline, width, height, self.max_width, self.max_height)
+                .ok_or_else(|| {
+                    format!(
+                        "[FerrousGl Error] Image \"{}\" does not fit into the {}x{} texture atlas.",
+                        name, self.max_width, self.max_height
+                    )
+                })?;
+
+            insert_skyline_segment(&mut skyline, index, x, y + height, width);
+            placements[i] = (x, y);
+            used_width = used_width.max(x + width);
+            used_height = used_height.max(y + height);
+        }
+
+        let mut atlas = RgbaImage::new(used_width.max(1), used_height.max(1));
+        let mut regions = BTreeMap::new();
+
+        for (i, (name, image)) in self.images.iter().enumerate() {
+            let (x, y) = placements[i];
+            let (inner_x, inner_y) = (x + self.extrude, y + self.extrude);
+            blit_extruded(&mut atlas, image, inner_x, inner_y, self.extrude);
+
+            regions.insert(
+                name.clone(),
+                AtlasRegion::new(inner_x, inner_y, image.width(), image.height(), atlas.width(), atlas.height()),
+            );
+        }
+
+        let layout = AtlasLayout {
+            width: atlas.width(),
+            height: atlas.height(),
+            regions,
+        };
+
+        Ok((atlas, layout))
+    }
+}
+
+/// A single texture containing many packed images, which can be looked up by name.
+pub struct TextureAtlas {
+    texture: Texture,
+    layout: AtlasLayout,
+}
+
+impl TextureAtlas {
+    /// Loads a prebuilt atlas from its image file and JSON layout file. Requires the `serde` feature.
+    #[cfg(feature = "serde")]
+    pub fn load(image_path: &Path, layout_path: &Path) -> Result<Self, String> {
+        let image = image::open(image_path).map_err(|e| e.to_string())?;
+        Self::from_image_and_layout(&image, AtlasLayout::load(layout_path)?)
+    }
+
+    /// Creates an atlas from an already packed image and its layout.
+    pub fn from_image_and_layout(image: &DynamicImage, layout: AtlasLayout) -> Result<Self, String> {
+        if image.width() != layout.width || image.height() != layout.height {
+            return Err(format!(
+                "[FerrousGl Error] Atlas image is {}x{}, but the layout expects {}x{}.",
+                image.width(),
+                image.height(),
+                layout.width,
+                layout.height
+            ));
+        }
+
+        Ok(TextureAtlas {
+            texture: Texture::from_image(image)?,
+            layout,
+        })
+    }
+
+    /// Returns the texture containing all packed images.
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// Returns the layout of the atlas.
+    pub fn layout(&self) -> &AtlasLayout {
+        &self.layout
+    }
+
+    /// Returns the region of a named image.
+    pub fn region(&self, name: &str) -> Option<&AtlasRegion> {
+        self.layout.regions.get(name)
+    }
+
+    /// Returns the minimum and maximum texture coordinates of a named image.
+    pub fn uv(&self, name: &str) -> Option<(Vec2, Vec2)> {
+        self.region(name).map(|region| (region.uv_min, region.uv_max))
+    }
+}
+
+/// A horizontal piece of the skyline, the top edge of everything placed below it.
+struct SkylineSegment {
+    x: u32,
+    y: u32,
+    width: u32,
+}
+
+/// Finds the lowest (then leftmost) position a rectangle fits at, returning the skyline index it starts at.
+fn find_skyline_position(
+    skyline: &[SkylineSegment],
+    width: u32,
+    height: u32,
+    max_width: u32,
+    max_height: u32,
+) -> Option<(usize, u32, u32)> {
+    let mut best: Option<(usize, u32, u32)> = None;
+
+    for (index, segment) in skyline.iter().enumerate() {
+        if segment.x + width > max_width {
+            break;
+        }
+
+        // The rectangle rests on the highest segment it spans
+        let mut y = 0;
+        let mut covered = 0;
+        for spanned in &skyline[index..] {
+            if covered >= width {
+                break;
+            }
+            y = y.max(spanned.y);
+            covered += spanned.width;
+        }
+
+        if y + height > max_height {
+            continue;
+        }
+
+        if best.is_none_or(|(_, best_x, best_y)| (y, segment.x) < (best_y, best_x)) {
+            best = Some((index, segment.x, y));
+        }
+    }
+
+    best
+}
+
+/// Raises the skyline where a rectangle has been placed, trimming the segments it covers.
+fn insert_skyline_segment(skyline: &mut Vec<SkylineSegment>, index: usize, x: u32, y: u32, width: u32) {
+    skyline.insert(index, SkylineSegment { x, y, width });
+
+    let right = x + width;
+    while index + 1 < skyline.len() && skyline[index + 1].x < right {
+        let next = &mut skyline[index + 1];
+        let overlap = right - next.x;
+        if overlap >= next.width {
+            skyline.remove(index + 1);
+        } else {
+            next.x += overlap;
+            next.width -= overlap;
+            break;
+        }
+    }
+
+    // Merge neighbouring segments at the same height
+    let mut i = 0;
+    while i + 1 < skyline.len() {
+        if skyline[i].y == skyline[i + 1].y {
+            skyline[i].width += skyline[i + 1].width;
+            skyline.remove(i + 1);
+        } else {
+            i += 1;
+        }
+    }
+}
+
+/// Copies an image into the atlas at (x, y), repeating its border pixels `extrude` pixels outwards.
+fn blit_extruded(atlas: &mut RgbaImage, image: &DynamicImage, x: u32, y: u32, extrude: u32) {
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let extrude = extrude as i64;
+    for dy in -extrude..height as i64 + extrude {
+        for dx in -extrude..width as i64 + extrude {
+            let source_x = dx.clamp(0, width as i64 - 1) as u32;
+            let source_y = dy.clamp(0, height as i64 - 1) as u32;
+            atlas.put_pixel(
+                (x as i64 + dx) as u32,
+                (y as i64 + dy) as u32,
+                image.get_pixel(source_x, source_y),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid_image(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_pixel(width, height, image::Rgba([255, 0, 0, 255])))
+    }
+
+    fn overlaps(a: &AtlasRegion, b: &AtlasRegion) -> bool {
+        a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn layout_json_round_trip_recalculates_uvs() {
+        let mut regions = BTreeMap::new();
+        regions.insert("grass".to_string(), AtlasRegion::new(0, 0, 16, 16, 64, 32));
+        regions.insert("stone".to_string(), AtlasRegion::new(16, 8, 32, 24, 64, 32));
+        let layout = AtlasLayout {
+            width: 64,
+            height: 32,
+            regions,
+        };
+
+        let json = layout.to_json();
+        assert!(!json.contains("uv_min"));
+        assert_eq!(AtlasLayout::from_json(&json).unwrap(), layout);
+        assert!(AtlasLayout::from_json(r#"{"width": 8, "height": 8}"#).is_err());
+    }
+
+    #[test]
+    fn packer_places_images_without_overlap() {
+        let sizes = [(30, 10), (12, 40), (7, 7), (25, 25), (3, 60), (64, 5), (9, 14), (18, 18)];
+        let mut builder = TextureAtlasBuilder::new(128, 128).padding(2).extrude(1);
+        for (i, &(width, height)) in sizes.iter().enumerate() {
+            builder = builder.add_image(&format!("image{}", i), solid_image(width, height));
+        }
+
+        let (atlas, layout) = builder.build_image().unwrap();
+        let regions: Vec<&AtlasRegion> = layout.regions.values().collect();
+        assert_eq!(regions.len(), sizes.len());
+
+        for (i, a) in regions.iter().enumerate() {
+            assert!(a.x >= 1 && a.y >= 1);
+            assert!(a.x + a.width < atlas.width() && a.y + a.height < atlas.height());
+            for b in &regions[i + 1..] {
+                assert!(!overlaps(a, b), "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn packer_fails_when_atlas_is_full() {
+        let builder = TextureAtlasBuilder::new(32, 32)
+            .add_image("a", solid_image(32, 20))
+            .add_image("b", solid_image(32, 20));
+        assert!(builder.build_image().is_err());
+
+        let builder = TextureAtlasBuilder::new(32, 32).add_image("wide", solid_image(33, 1));
+        assert!(builder.build_image().is_err());
+
+        let builder = TextureAtlasBuilder::new(32, 32)
+            .add_image("a", solid_image(16, 32))
+            .add_image("b", solid_image(16, 32));
+        assert!(builder.build_image().is_ok());
+    }
+}
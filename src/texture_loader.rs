@@ -0,0 +1,265 @@
+extern crate gl;
+extern crate image;
+
+use gl::types::*;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::compressed_texture::CompressedImage;
+use crate::texture::{PreparedImage, TextureOptions};
+use crate::Texture;
+
+/// The number of pixel unpack buffers uploads rotate through, so filling one doesn't wait for the GPU
+/// to finish copying from the previous upload.
+const PIXEL_BUFFER_COUNT: usize = 2;
+
+/// The state of a texture requested from a [`TextureLoader`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextureLoadState {
+    /// The image is still being decoded or waiting to be uploaded.
+    Loading,
+    /// The texture has been uploaded and can be used.
+    Ready,
+    /// Decoding failed, with the error message.
+    Failed(String),
+}
+
+enum LoadSlot {
+    Loading,
+    Ready(Rc<Texture>),
+    Failed(String),
+}
+
+/// A handle to a texture that is loaded in the background. Handles can be cloned and all clones
+/// see the texture once [`TextureLoader::process_uploads`] has uploaded it.
+#[derive(Clone)]
+pub struct TextureHandle {
+    slot: Rc<RefCell<LoadSlot>>,
+}
+
+impl TextureHandle {
+    /// Returns whether the texture is still loading, ready or has failed to load.
+    pub fn state(&self) -> TextureLoadState {
+        match &*self.slot.borrow() {
+            LoadSlot::Loading => TextureLoadState::Loading,
+            LoadSlot::Ready(_) => TextureLoadState::Ready,
+            LoadSlot::Failed(error) => TextureLoadState::Failed(error.clone()),
+        }
+    }
+
+    /// Returns true once the texture can be used.
+    pub fn is_ready(&self) -> bool {
+        matches!(*self.slot.borrow(), LoadSlot::Ready(_))
+    }
+
+    /// Returns the texture once it has been uploaded.
+    pub fn texture(&self) -> Option<Rc<Texture>> {
+        match &*self.slot.borrow() {
+            LoadSlot::Ready(texture) => Some(texture.clone()),
+            _ => None,
+        }
+    }
+}
+
+struct LoadJob {
+    id: u64,
+    path: PathBuf,
+    options: TextureOptions,
+}
+
+enum DecodedImage {
+    Image(PreparedImage),
+    Compressed(CompressedImage),
+}
+
+struct LoadResult {
+    id: u64,
+    result: Result<DecodedImage, String>,
+}
+
+/// Loads textures without blocking the render thread. Image files are decoded (and converted, including
+/// CPU generated mipmaps) on a pool of worker threads, while the OpenGL uploads happen on the main thread in
+/// [`TextureLoader::process_uploads`], which should be called once per frame with a time budget.
+pub struct TextureLoader {
+    job_sender: Option<Sender<LoadJob>>,
+    result_receiver: Receiver<LoadResult>,
+    workers: Vec<JoinHandle<()>>,
+    pending: HashMap<u64, Rc<RefCell<LoadSlot>>>,
+    decoded: VecDeque<LoadResult>,
+    next_id: u64,
+    pixel_buffers: Vec<GLuint>,
+    next_pixel_buffer: usize,
+}
+
+impl TextureLoader {
+    /// Creates a new texture loader that decodes images on the given number of worker threads.
+    pub fn new(worker_count: usize) -> Self {
+        let (job_sender, job_receiver) = mpsc::channel::<LoadJob>();
+        let (result_sender, result_receiver) = mpsc::channel();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+
+        let workers = (0..worker_count.max(1))
+            .map(|_| {
+                let job_receiver = Arc::clone(&job_receiver);
+                let result_sender = result_sender.clone();
+
+                thread::spawn(move || loop {
+                    // The lock is released as soon as a job has been received
+                    let job = match job_receiver.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => break,
+                    };
+
+                    // The channel closes when the loader is dropped
+                    let Ok(job) = job else {
+                        break;
+                    };
+
+                    let result = decode(&job.path, job.options);
+                    if result_sender.send(LoadResult { id: job.id, result }).is_err() {
+                        break;
+                    }
+                })
+            })
+            .collect();
+
+        TextureLoader {
+            job_sender: Some(job_sender),
+            result_receiver,
+            workers,
+            pending: HashMap::new(),
+            decoded: VecDeque::new(),
+            next_id: 0,
+            pixel_buffers: Vec::new(),
+            next_pixel_buffer: 0,
+        }
+    }
+
+    /// Enables or disables uploading through pixel unpack buffers (PBOs), which lets the driver copy the
+    /// pixel data to the GPU asynchronously instead of stalling inside `glTexImage2D`. Uploads alternate
+    /// between two buffers, so the next image can be written while the previous one is still being copied.
+    pub fn set_use_pixel_buffers(&mut self, enabled: bool) {
+        if enabled && self.pixel_buffers.is_empty() {
+            self.pixel_buffers = vec![0; PIXEL_BUFFER_COUNT];
+            unsafe {
+                gl::GenBuffers(PIXEL_BUFFER_COUNT as GLsizei, self.pixel_buffers.as_mut_ptr());
+            }
+        } else if !enabled && !self.pixel_buffers.is_empty() {
+            unsafe {
+                gl::DeleteBuffers(self.pixel_buffers.len() as GLsizei, self.pixel_buffers.as_ptr());
+            }
+            self.pixel_buffers.clear();
+        }
+        self.next_pixel_buffer = 0;
+    }
+
+    /// Starts loading a texture from an image file in the background, see [`Texture::new_from_file`].
+    pub fn load(&mut self, path: &Path) -> TextureHandle {
+        self.load_with_options(path, TextureOptions::default())
+    }
+
+    /// Starts loading a texture from an image file in the background, using the given format and mipmap options.
+    /// DDS and KTX2 files are uploaded as they are and ignore the options.
+    pub fn load_with_options(&mut self, path: &Path, options: TextureOptions) -> TextureHandle {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let slot = Rc::new(RefCell::new(LoadSlot::Loading));
+        let job = LoadJob {
+            id,
+            path: path.to_path_buf(),
+            options,
+        };
+
+        let sent = self
+            .job_sender
+            .as_ref()
+            .is_some_and(|sender| sender.send(job).is_ok());
+
+        if sent {
+            self.pending.insert(id, slot.clone());
+        } else {
+            *slot.borrow_mut() = LoadSlot::Failed("[FerrousGl Error] The texture loader worker threads have stopped.".to_string());
+        }
+
+        TextureHandle { slot }
+    }
+
+    /// Uploads decoded images until the time budget is used up, returning how many textures were uploaded.
+    /// At least one texture is uploaded per call if any are waiting, so loading always makes progress.
+    pub fn process_uploads(&mut self, budget: Duration) -> usize {
+        let start = Instant::now();
+        self.decoded.extend(self.result_receiver.try_iter());
+
+        let mut uploaded = 0;
+        while uploaded == 0 || start.elapsed() < budget {
+            let Some(LoadResult { id, result }) = self.decoded.pop_front() else {
+                break;
+            };
+
+            let Some(slot) = self.pending.remove(&id) else {
+                continue;
+            };
+
+            let state = match result {
+                Ok(DecodedImage::Image(prepared)) => {
+                    let pixel_buffer = self.pixel_buffers.get(self.next_pixel_buffer).copied();
+                    self.next_pixel_buffer = (self.next_pixel_buffer + 1) % PIXEL_BUFFER_COUNT;
+                    LoadSlot::Ready(Rc::new(Texture::from_prepared(&prepared, pixel_buffer)))
+                }
+                Ok(DecodedImage::Compressed(compressed)) => match Texture::from_compressed(&compressed) {
+                    Ok(texture) => LoadSlot::Ready(Rc::new(texture)),
+                    Err(error) => LoadSlot::Failed(error),
+                },
+                Err(error) => LoadSlot::Failed(error),
+            };
+
+            *slot.borrow_mut() = state;
+            uploaded += 1;
+        }
+
+        uploaded
+    }
+
+    /// Returns the number of textures that are still decoding or waiting to be uploaded.
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+impl Default for TextureLoader {
+    /// Creates a texture loader with one worker thread per available CPU core, leaving one for the render thread.
+    fn default() -> Self {
+        let cores = thread::available_parallelism().map(|n| n.get()).unwrap_or(2);
+        Self::new(cores.saturating_sub(1))
+    }
+}
+
+impl Drop for TextureLoader {
+    /// Stops the worker threads and cleans up the pixel unpack buffers.
+    fn drop(&mut self) {
+        // Closing the job channel lets the workers finish their current image and exit
+        self.job_sender = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+
+        self.set_use_pixel_buffers(false);
+    }
+}
+
+/// Decodes an image file on a worker thread.
+fn decode(path: &Path, options: TextureOptions) -> Result<DecodedImage, String> {
+    if CompressedImage::is_container(path) {
+        return CompressedImage::open(path).map(DecodedImage::Compressed);
+    }
+
+    let img = image::open(path).map_err(|e| e.to_string())?;
+    PreparedImage::new(&img, options).map(DecodedImage::Image)
+}
@@ -2,14 +2,19 @@ extern crate gl;
 extern crate glam;
 extern crate glfw;
 
+use std::cell::Cell;
 use std::collections::HashSet;
 use std::ptr;
+use std::rc::Rc;
 
 use glam::{bool, Vec4};
 use glfw::{fail_on_errors, Context, Key, WindowEvent};
 use std::time::{Duration, Instant};
 
-use crate::{Mesh, Texture};
+use crate::framebuffer::{blit, preserve_framebuffer_bindings, BlitBuffers, Framebuffer, Rect};
+use crate::readback::{check_rect, read_framebuffer, PixelComponent, PixelFormat, PixelReadback};
+use crate::texture::{check_pixel_format, FilterMode, TextureFormat};
+use crate::{Camera, Material, Mesh, RenderTexture, RenderTextureConfig, Scene, Texture};
 
 /// A struct to manage an OpenGL context, window, rendering and input!
 pub struct GlWindow {
@@ -26,6 +31,8 @@ pub struct GlWindow {
     previous_pressed_keys: HashSet<WindowKey>,
     fb_texture: Texture,       // Persistent framebuffer texture
     depth_texture: Texture,    // Persistent depth texture
+    scene_target: Option<RenderTexture>, // Offscreen scene framebuffer, presented in update()
+    reverse_z: Cell<bool>,
 }
 
 impl GlWindow {
@@ -39,7 +46,11 @@ impl GlWindow {
         glfw.window_hint(glfw::WindowHint::Decorated(config.decorated));
         glfw.window_hint(glfw::WindowHint::Resizable(config.resizeable));
         glfw.window_hint(glfw::WindowHint::DoubleBuffer(true));
-        glfw.window_hint(glfw::WindowHint::Samples(Some(config.anti_aliasing)));
+        // With a scene framebuffer, anti-aliasing happens there and the window itself is only blitted to
+        let window_samples = if config.scene_framebuffer { 0 } else { config.anti_aliasing };
+        glfw.window_hint(glfw::WindowHint::Samples(Some(window_samples)));
+        glfw.window_hint(glfw::WindowHint::DepthBits(Some(24)));
+        glfw.window_hint(glfw::WindowHint::StencilBits(Some(8)));
 
         let (mut window, events) = glfw
             .create_window(
@@ -62,6 +73,8 @@ impl GlWindow {
         unsafe {
             gl::Enable(gl::DEPTH_TEST);
             gl::Enable(gl::MULTISAMPLE);
+            // Filter cubemaps across face edges instead of clamping at them
+            gl::Enable(gl::TEXTURE_CUBE_MAP_SEAMLESS);
             gl::Viewport(0, 0, config.width as i32, config.height as i32);
         }
 
@@ -71,37 +84,33 @@ impl GlWindow {
             samples
         };
 
-        if actual_samples == 0 {
+        if actual_samples == 0 && window_samples > 0 {
             println!("[FerrousGl Error] MSAA Configuration has failed. This is likely a problem with your nvidia driver.\nYou can change the problematic setting by going into NVIDIA Control Panel > Manage 3D Settings and clicking restore.");
         }
 
         let fb_texture = Texture::new_empty(config.width, config.height)
             .expect("Failed to create framebuffer texture");
-        let depth_texture = Texture::new_empty(config.width, config.height)
+        let depth_texture = Texture::new_empty_with_format(config.width, config.height, TextureFormat::Depth32F)
             .expect("Failed to create depth texture");
 
-        // Configure depth texture format
-        // In the new() function, modify depth texture creation:
-        unsafe {
-            gl::BindTexture(gl::TEXTURE_2D, depth_texture.id);
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::DEPTH_COMPONENT32F as i32,  // Use 32-bit float format
-                config.width as i32,
-                config.height as i32,
-                0,
-                gl::DEPTH_COMPONENT,
-                gl::FLOAT,
-                ptr::null(),
-            );
-            // Set texture parameters
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
-            gl::BindTexture(gl::TEXTURE_2D, 0);
-        }
+        let scene_target = if config.scene_framebuffer {
+            let (width, height) = window.get_framebuffer_size();
+            let scene_target = RenderTexture::new_with_config(RenderTextureConfig {
+                width: width as u32,
+                height: height as u32,
+                color_formats: vec![TextureFormat::Rgba8],
+                depth_format: Some(TextureFormat::Depth24Stencil8),
+                samples: config.anti_aliasing,
+            })
+            .expect("[FerrousGl Error] Failed to create scene framebuffer.");
+
+            unsafe {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, scene_target.draw_framebuffer_id());
+            }
+            Some(scene_target)
+        } else {
+            None
+        };
 
         GlWindow {
             glfw,
@@ -117,6 +126,8 @@ impl GlWindow {
             previous_pressed_keys: HashSet::new(),
             fb_texture,
             depth_texture,
+            scene_target,
+            reverse_z: Cell::new(false),
         }
     }
 
@@ -189,6 +200,57 @@ impl GlWindow {
         }
     }
 
+    /// Switches depth between the standard mapping and reverse-Z, which maps the near plane to 1 and the far plane to 0.
+    /// Sets the clip range, depth clear value and depth test to match, so use it together with `Camera::reverse_z`.
+    /// Requires `glClipControl` (OpenGL 4.5 or `ARB_clip_control`).
+    pub fn set_reverse_z(&self, enabled: bool) -> Result<(), String> {
+        if !gl::ClipControl::is_loaded() {
+            return Err("[FerrousGl Error] Reverse-Z requires glClipControl, which this OpenGL context does not support.".to_string());
+        }
+
+        unsafe {
+            if enabled {
+                gl::ClipControl(gl::LOWER_LEFT, gl::ZERO_TO_ONE);
+                gl::ClearDepth(0.0);
+                gl::DepthFunc(gl::GREATER);
+            } else {
+                gl::ClipControl(gl::LOWER_LEFT, gl::NEGATIVE_ONE_TO_ONE);
+                gl::ClearDepth(1.0);
+                gl::DepthFunc(gl::LESS);
+            }
+        }
+
+        self.reverse_z.set(enabled);
+        Ok(())
+    }
+
+    /// Returns true if reverse-Z has been enabled with [`GlWindow::set_reverse_z`].
+    pub fn is_reverse_z(&self) -> bool {
+        self.reverse_z.get()
+    }
+
+    /// Set the stencil test, which can discard fragments based on the stencil buffer and update it while drawing.
+    /// Useful for outlines, portals and masking.
+    pub fn set_stencil_state(&self, stencil_state: StencilState) {
+        unsafe {
+            let (front, back) = match stencil_state {
+                StencilState::Disabled => {
+                    gl::Disable(gl::STENCIL_TEST);
+                    return;
+                }
+                StencilState::Both(face) => (face, face),
+                StencilState::Separate { front, back } => (front, back),
+            };
+
+            gl::Enable(gl::STENCIL_TEST);
+            for (gl_face, face) in [(gl::FRONT, front), (gl::BACK, back)] {
+                gl::StencilFuncSeparate(gl_face, face.func.into(), face.reference, face.read_mask);
+                gl::StencilOpSeparate(gl_face, face.fail.into(), face.depth_fail.into(), face.pass.into());
+                gl::StencilMaskSeparate(gl_face, face.write_mask);
+            }
+        }
+    }
+
     /// Set the preferred Blending Mode.
     pub fn set_blend_mode(&self, blend_mode: BlendMode) {
         unsafe {
@@ -228,36 +290,109 @@ impl GlWindow {
 
     /// Updates the framebuffer and depth texture. You probably want to do this after rendering anything. 
     /// Clearing the depth buffer or color buffer will not clear these textures.
+    /// With a scene framebuffer (see [`WindowConfig::scene_framebuffer`]) the textures are always up to date,
+    /// and this only resolves multisampling.
     pub fn update_framebuffer_textures(&mut self) {
-        let (width, height) = self.get_window_size();
+        if let Some(scene_target) = &self.scene_target {
+            scene_target.resolve();
+            return;
+        }
+
+        let (width, height) = self.get_framebuffer_size();
+        let (width, height) = (width.max(1) as u32, height.max(1) as u32);
+
+        // Only reallocate when the window has been resized
+        if self.fb_texture.width() != width || self.fb_texture.height() != height {
+            self.fb_texture.resize(width, height);
+            self.depth_texture.resize(width, height);
+        }
         
         unsafe {
             // Update color texture
             gl::BindTexture(gl::TEXTURE_2D, self.fb_texture.id);
-            gl::CopyTexImage2D(
-                gl::TEXTURE_2D, 0, gl::RGBA,
-                0, 0, width, height, 0
+            gl::CopyTexSubImage2D(
+                gl::TEXTURE_2D, 0,
+                0, 0, 0, 0, width as i32, height as i32
             );
             
             // Update depth texture
             gl::BindTexture(gl::TEXTURE_2D, self.depth_texture.id);
-            gl::CopyTexImage2D(
-                gl::TEXTURE_2D, 0, gl::DEPTH_COMPONENT,
-                0, 0, width, height, 0
+            gl::CopyTexSubImage2D(
+                gl::TEXTURE_2D, 0,
+                0, 0, 0, 0, width as i32, height as i32
             );
             
             gl::BindTexture(gl::TEXTURE_2D, 0);
         }
     }
 
+    /// Reads a rectangle of the window's framebuffer, blocking until rendering has finished.
+    /// Rows start at the bottom of the rectangle.
+    pub fn read_pixels<T: PixelComponent>(&self, rect: Rect, format: PixelFormat) -> Result<Vec<T>, String> {
+        self.check_readable(rect, format)?;
+        Ok(read_framebuffer(self.read_framebuffer_id(), None, rect, format))
+    }
+
+    /// Starts reading a rectangle of the window's framebuffer in the background, see [`PixelReadback`].
+    pub fn read_pixels_async<T: PixelComponent>(&self, rect: Rect, format: PixelFormat) -> Result<PixelReadback<T>, String> {
+        self.check_readable(rect, format)?;
+        Ok(PixelReadback::start(self.read_framebuffer_id(), None, rect, format))
+    }
+
+    /// Checks that the rectangle lies inside the framebuffer and that the format can be read from it.
+    fn check_readable(&self, rect: Rect, format: PixelFormat) -> Result<(), String> {
+        if let Some(scene_target) = &self.scene_target {
+            return scene_target.check_readable(rect, format).map(|_| ());
+        }
+
+        let (width, height) = self.get_framebuffer_size();
+        check_rect(rect, width as u32, height as u32)?;
+
+        // The window's color buffer is always normalized, only the depth and stencil buffers may be missing
+        let surface_format = if format.is_depth_or_stencil() {
+            let depth_bits = default_framebuffer_bits(gl::DEPTH, gl::FRAMEBUFFER_ATTACHMENT_DEPTH_SIZE);
+            let stencil_bits = default_framebuffer_bits(gl::STENCIL, gl::FRAMEBUFFER_ATTACHMENT_STENCIL_SIZE);
+            match (depth_bits > 0, stencil_bits > 0) {
+                (true, true) => TextureFormat::Depth24Stencil8,
+                (true, false) => TextureFormat::Depth24,
+                (false, _) => return Err("[FerrousGl Error] The window has no depth buffer to read from.".to_string()),
+            }
+        } else {
+            TextureFormat::Rgba8
+        };
+        check_pixel_format(surface_format, format)
+    }
+
+    /// Returns the framebuffer that holds the rendered scene, resolving the scene framebuffer if there is one.
+    fn read_framebuffer_id(&self) -> gl::types::GLuint {
+        match &self.scene_target {
+            Some(scene_target) => {
+                scene_target.resolve();
+                scene_target.framebuffer_id
+            }
+            None => 0,
+        }
+    }
+
     /// Returns the framebuffer texture.
     pub fn get_framebuffer_texture(&self) -> &Texture {
-        &self.fb_texture
+        match &self.scene_target {
+            Some(scene_target) => scene_target.texture(),
+            None => &self.fb_texture,
+        }
     }
 
     /// Returns the depth texture.
     pub fn get_depth_texture(&self) -> &Texture {
-        &self.depth_texture
+        match self.scene_target.as_ref().and_then(|scene_target| scene_target.depth_texture()) {
+            Some(depth_texture) => depth_texture,
+            None => &self.depth_texture,
+        }
+    }
+
+    /// Returns the render texture the scene is rendered into, if the window was created with a scene framebuffer.
+    pub fn get_scene_render_texture(&self) -> Option<&RenderTexture> {
+        self.scene_target.as_ref()
     }
 
     /// Get the current clipboard string.
@@ -275,6 +410,11 @@ impl GlWindow {
         self.window.get_size()
     }
 
+    /// Returns the size of the window's framebuffer in pixels, which can differ from the window size on high DPI screens.
+    pub fn get_framebuffer_size(&self) -> (i32, i32) {
+        self.window.get_framebuffer_size()
+    }
+
     /// Returns the windows position.
     pub fn get_window_position(&self) -> (i32, i32) {
         self.window.get_pos()
@@ -389,6 +529,9 @@ impl GlWindow {
         self.update_pressed_keys();
         self.last_mouse_position = self.get_mouse_position();
 
+        self.present_scene_framebuffer();
+
+        let mut resized_framebuffer = None;
         self.glfw.poll_events();
         for (_, event) in glfw::flush_messages(&self.events) {
             match event {
@@ -402,6 +545,7 @@ impl GlWindow {
                 }
                 WindowEvent::FramebufferSize(width, height) => {
                     self.update_viewport(width, height);
+                    resized_framebuffer = Some((width, height));
                 }
                 WindowEvent::Scroll(xoffset, yoffset) => {
                     self.mouse_wheel_delta = (xoffset, yoffset);
@@ -414,6 +558,10 @@ impl GlWindow {
         }
         self.window.swap_buffers();
 
+        if let (Some(scene_target), Some((width, height))) = (&mut self.scene_target, resized_framebuffer) {
+            scene_target.resize(width.max(1) as u32, height.max(1) as u32);
+        }
+
         // Calculate frame time and sleep if needed (precise)
         let frame_time = frame_start.elapsed();
         if frame_time < self.target_frame_time {
@@ -432,8 +580,25 @@ impl GlWindow {
         self.last_frame_time = frame_start;
     }
 
-    /// Updates the OpenGL viewport to match a new window size. This function typically only needs to be used after a
-    /// render texture (or offscreen texture) that has a different size than the window is unbound.
+    /// Copies the scene framebuffer into the window, so it is shown by the next buffer swap.
+    fn present_scene_framebuffer(&self) {
+        let Some(scene_target) = &self.scene_target else {
+            return;
+        };
+
+        scene_target.resolve();
+        let (width, height) = self.get_framebuffer_size();
+        let _ = blit(
+            Framebuffer::RenderTexture(scene_target),
+            Framebuffer::Default,
+            Rect::from_size(scene_target.width(), scene_target.height()),
+            Rect::from_size(width.max(0) as u32, height.max(0) as u32),
+            FilterMode::Linear,
+            BlitBuffers::COLOR,
+        );
+    }
+
+    /// Updates the OpenGL viewport to match a new window size, for example after the window has been resized.
     pub fn update_viewport(&self, width: i32, height: i32) {
         unsafe {
             gl::Viewport(0, 0, width, height);
@@ -455,6 +620,15 @@ impl GlWindow {
         }
     }
 
+    /// Clears the current bound stencil buffer to the specified value. Only the bits enabled in the
+    /// front face's stencil write mask are cleared.
+    pub fn clear_stencil(&self, value: i32) {
+        unsafe {
+            gl::ClearStencil(value);
+            gl::Clear(gl::STENCIL_BUFFER_BIT);
+        }
+    }
+
     /// Renders a mesh using the provided shader and vertex data onto the current bound framebuffer.
     pub fn render_mesh(&self, mesh: &Mesh) {
         unsafe {
@@ -482,6 +656,58 @@ impl GlWindow {
             mesh.unbind();
         }
     }
+
+    /// Draws every visible node of a scene that has a mesh and a material onto the current bound framebuffer.
+    /// Each node's material is applied before drawing it, and its shader additionally receives the `model`, `view`
+    /// and `projection` matrices and the camera position as `viewPos`.
+    pub fn render(&self, scene: &Scene, camera: &Camera) {
+        let view = camera.view_matrix().to_cols_array();
+        let projection = camera.projection_matrix_for_window(self).to_cols_array();
+        let camera_position = camera.position;
+
+        let mut bound_material: Option<&Rc<Material>> = None;
+        for (id, mesh, material) in scene.drawables() {
+            material.apply();
+
+            // Uniforms are stored per program, so the camera only has to be uploaded when the shader changes
+            let shader = material.shader();
+            if !bound_material.is_some_and(|bound| bound.shares_shader(material)) {
+                shader.set_uniform_matrix_4fv("view", &view);
+                shader.set_uniform_matrix_4fv("projection", &projection);
+                shader.set_uniform_3f("viewPos", camera_position.x, camera_position.y, camera_position.z);
+            }
+            bound_material = Some(material);
+
+            shader.set_uniform_matrix_4fv("model", &scene.world_matrix(id).to_cols_array());
+            self.render_mesh(mesh);
+        }
+
+        if let Some(material) = bound_material {
+            material.unbind();
+        }
+    }
+}
+
+/// Returns the size in bits of the default framebuffer's depth or stencil buffer, 0 if it has none.
+fn default_framebuffer_bits(attachment: gl::types::GLenum, size: gl::types::GLenum) -> i32 {
+    preserve_framebuffer_bindings(|| unsafe {
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+
+        let mut object_type = 0;
+        gl::GetFramebufferAttachmentParameteriv(
+            gl::READ_FRAMEBUFFER,
+            attachment,
+            gl::FRAMEBUFFER_ATTACHMENT_OBJECT_TYPE,
+            &mut object_type,
+        );
+        if object_type as gl::types::GLenum == gl::NONE {
+            return 0;
+        }
+
+        let mut bits = 0;
+        gl::GetFramebufferAttachmentParameteriv(gl::READ_FRAMEBUFFER, attachment, size, &mut bits);
+        bits
+    })
 }
 
 /// Struct to more easily allow setting window features.
@@ -495,6 +721,10 @@ pub struct WindowConfig {
     pub target_framerate: u32,
     pub transparent_framebuffer: bool,
     pub anti_aliasing: u32,
+    /// Renders into an owned color and depth-stencil render texture instead of the window, which is copied into the
+    /// window by [`GlWindow::update`]. Its textures can be sampled at any time and follow the window size.
+    /// `Framebuffer::Default` still refers to the window itself, which is overwritten when the scene is presented.
+    pub scene_framebuffer: bool,
 }
 
 impl Default for WindowConfig {
@@ -508,6 +738,7 @@ impl Default for WindowConfig {
             target_framerate: 60,
             transparent_framebuffer: false,
             anti_aliasing: 4,
+            scene_framebuffer: false,
         }
     }
 }
@@ -599,6 +830,125 @@ impl From<DepthType> for Option<gl::types::GLenum> {
     }
 }
 
+/// Enum representing the stencil test, which can differ between front and back facing triangles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StencilState {
+    Disabled,
+    Both(StencilFaceState),
+    Separate {
+        front: StencilFaceState,
+        back: StencilFaceState,
+    },
+}
+
+/// The stencil test and stencil buffer updates for one face. A fragment passes the test if
+/// `reference & read_mask` compares to `stencil & read_mask` with `func`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StencilFaceState {
+    pub func: StencilFunc,
+    pub reference: i32,
+    pub read_mask: u32,
+    /// The bits of the stencil buffer that can be written.
+    pub write_mask: u32,
+    /// What happens to the stencil value when the stencil test fails.
+    pub fail: StencilOp,
+    /// What happens to the stencil value when the stencil test passes but the depth test fails.
+    pub depth_fail: StencilOp,
+    /// What happens to the stencil value when both tests pass.
+    pub pass: StencilOp,
+}
+
+impl StencilFaceState {
+    /// Always passes and writes `reference` wherever something is drawn, for example to mark an object before drawing its outline.
+    pub fn write(reference: i32) -> Self {
+        Self {
+            pass: StencilOp::Replace,
+            reference,
+            ..Default::default()
+        }
+    }
+
+    /// Only draws where the stencil value compares to `reference` with `func`, without changing the stencil buffer.
+    pub fn test(func: StencilFunc, reference: i32) -> Self {
+        Self {
+            func,
+            reference,
+            write_mask: 0,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for StencilFaceState {
+    fn default() -> Self {
+        Self {
+            func: StencilFunc::Always,
+            reference: 0,
+            read_mask: 0xFF,
+            write_mask: 0xFF,
+            fail: StencilOp::Keep,
+            depth_fail: StencilOp::Keep,
+            pass: StencilOp::Keep,
+        }
+    }
+}
+
+/// Enum representing different stencil test functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StencilFunc {
+    Never,
+    Less,
+    Equal,
+    LessOrEqual,
+    Greater,
+    NotEqual,
+    GreaterOrEqual,
+    Always,
+}
+
+impl From<StencilFunc> for gl::types::GLenum {
+    fn from(func: StencilFunc) -> Self {
+        match func {
+            StencilFunc::Never => gl::NEVER,
+            StencilFunc::Less => gl::LESS,
+            StencilFunc::Equal => gl::EQUAL,
+            StencilFunc::LessOrEqual => gl::LEQUAL,
+            StencilFunc::Greater => gl::GREATER,
+            StencilFunc::NotEqual => gl::NOTEQUAL,
+            StencilFunc::GreaterOrEqual => gl::GEQUAL,
+            StencilFunc::Always => gl::ALWAYS,
+        }
+    }
+}
+
+/// Enum representing the ways the stencil buffer can be updated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StencilOp {
+    Keep,
+    Zero,
+    Replace,
+    Increment,
+    IncrementWrap,
+    Decrement,
+    DecrementWrap,
+    Invert,
+}
+
+impl From<StencilOp> for gl::types::GLenum {
+    fn from(op: StencilOp) -> Self {
+        match op {
+            StencilOp::Keep => gl::KEEP,
+            StencilOp::Zero => gl::ZERO,
+            StencilOp::Replace => gl::REPLACE,
+            StencilOp::Increment => gl::INCR,
+            StencilOp::IncrementWrap => gl::INCR_WRAP,
+            StencilOp::Decrement => gl::DECR,
+            StencilOp::DecrementWrap => gl::DECR_WRAP,
+            StencilOp::Invert => gl::INVERT,
+        }
+    }
+}
+
 /// Enum storing all different rendering types.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RenderingType {